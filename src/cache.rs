@@ -2,7 +2,20 @@ use std::path::PathBuf;
 
 use sanitize_filename::sanitize;
 
-use crate::{config::lyrics_path, error::LyricsError, song::SongInfo};
+use crate::{
+    config::lyrics_path,
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo},
+};
+
+/// 可读取的歌词文件后缀，按优先级排列
+const LYRICS_EXTS: [&str; 5] = ["lrc", "srt", "vtt", "ttml", "txt"];
+/// 翻译文件后缀
+const TRANSLATION_EXT: &str = "trans.lrc";
+/// 罗马音文件后缀
+const ROMANIZATION_EXT: &str = "roma.lrc";
+/// 用户调整的偏移（毫秒），不改动歌词文件
+const OFFSET_EXT: &str = "offset";
 
 // 缓存管理模块
 #[derive(Debug, Clone, Default)]
@@ -17,41 +30,150 @@ impl CacheManager {
         }
     }
 
-    fn lyrics_name(&self, song: &SongInfo) -> PathBuf {
+    fn lyrics_name(&self, song: &SongInfo, ext: &str) -> PathBuf {
         let mut name = vec![sanitize(&song.artist), sanitize(&song.title)];
         if !song.album.is_empty() {
             name.push(sanitize(&song.album));
         }
-        let file_name = format!("{}.lrc", name.join("-"));
+        let file_name = format!("{}.{}", name.join("-"), ext);
         let mut path = self.base_dir.clone();
         path.push(file_name);
         path
     }
 
-    pub async fn get(&self, song: &SongInfo) -> Option<String> {
-        let path = self.lyrics_name(song);
-        if !path.exists() {
-            return None;
-        }
-        tokio::fs::read_to_string(&path).await.ok()
+    pub async fn get(&self, song: &SongInfo) -> Option<LyricsDoc> {
+        // 用户放入的 SRT/VTT/TTML 或纯文本歌词
+        let path = LYRICS_EXTS
+            .iter()
+            .map(|ext| self.lyrics_name(song, ext))
+            .find(|path| path.exists())?;
+        let lyric = tokio::fs::read_to_string(&path).await.ok()?;
+
+        let translation = tokio::fs::read_to_string(self.lyrics_name(song, TRANSLATION_EXT))
+            .await
+            .ok();
+        let romanization = tokio::fs::read_to_string(self.lyrics_name(song, ROMANIZATION_EXT))
+            .await
+            .ok();
+
+        Some(LyricsDoc {
+            lyric,
+            translation,
+            romanization,
+        })
     }
 
     pub async fn store(
         &self,
         song: &SongInfo,
         _source: &str,
-        content: &str,
+        doc: &LyricsDoc,
+    ) -> Result<(), LyricsError> {
+        let path = self.lyrics_name(song, "lrc");
+        tokio::fs::write(path, &doc.lyric).await?;
+
+        // 翻译和罗马音与原文并列存放，没有时清除旧文件
+        self.store_extra(song, TRANSLATION_EXT, &doc.translation)
+            .await?;
+        self.store_extra(song, ROMANIZATION_EXT, &doc.romanization)
+            .await?;
+        // 新歌词的时间轴不同，清除旧的偏移
+        self.store_extra(song, OFFSET_EXT, &None).await?;
+        Ok(())
+    }
+
+    /// 读取用户调整的偏移，没有时为 0
+    pub async fn offset(&self, song: &SongInfo) -> i64 {
+        tokio::fs::read_to_string(self.lyrics_name(song, OFFSET_EXT))
+            .await
+            .ok()
+            .and_then(|s| s.trim().parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// 保存用户调整的偏移，为 0 时删除
+    pub async fn store_offset(&self, song: &SongInfo, offset: i64) -> Result<(), LyricsError> {
+        let content = (offset != 0).then(|| offset.to_string());
+        self.store_extra(song, OFFSET_EXT, &content).await
+    }
+
+    async fn store_extra(
+        &self,
+        song: &SongInfo,
+        ext: &str,
+        content: &Option<String>,
     ) -> Result<(), LyricsError> {
-        let path = self.lyrics_name(song);
-        tokio::fs::write(path, &content).await?;
+        let path = self.lyrics_name(song, ext);
+        match content {
+            Some(content) => tokio::fs::write(path, content).await?,
+            None => {
+                tokio::fs::remove_file(path).await.ok();
+            }
+        }
         Ok(())
     }
 
     pub async fn delete(&self, song: &SongInfo) {
-        let path = self.lyrics_name(song);
+        let path = self.lyrics_name(song, "lrc");
         match tokio::fs::remove_file(path).await {
             Ok(_) => {}
             Err(e) => tracing::error!("delete file {} failed {}", song.title, e),
         }
+        for ext in LYRICS_EXTS[1..]
+            .iter()
+            .chain(&[TRANSLATION_EXT, ROMANIZATION_EXT, OFFSET_EXT])
+        {
+            tokio::fs::remove_file(self.lyrics_name(song, ext))
+                .await
+                .ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn song() -> SongInfo {
+        SongInfo {
+            title: "title".to_string(),
+            artist: "artist".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn offset_is_kept_beside_the_lyrics() {
+        let base_dir =
+            std::env::temp_dir().join(format!("lyrics-next-cache-{}", std::process::id()));
+        tokio::fs::create_dir_all(&base_dir).await.unwrap();
+        let cache = CacheManager { base_dir };
+        let song = song();
+
+        // 用户放入的 SRT 不会被改写
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nline\n";
+        tokio::fs::write(cache.lyrics_name(&song, "srt"), srt)
+            .await
+            .unwrap();
+        cache.store_offset(&song, 300).await.unwrap();
+        assert_eq!(cache.offset(&song).await, 300);
+        assert_eq!(cache.get(&song).await.unwrap().lyric, srt);
+        assert!(!cache.lyrics_name(&song, "lrc").exists());
+
+        cache.store_offset(&song, 0).await.unwrap();
+        assert_eq!(cache.offset(&song).await, 0);
+        assert!(!cache.lyrics_name(&song, OFFSET_EXT).exists());
+
+        // 重新下载歌词时清除偏移
+        cache.store_offset(&song, -200).await.unwrap();
+        let doc = LyricsDoc {
+            lyric: "[00:01.00]line".to_string(),
+            ..Default::default()
+        };
+        cache.store(&song, "netease", &doc).await.unwrap();
+        assert_eq!(cache.offset(&song).await, 0);
+
+        cache.delete(&song).await;
+        tokio::fs::remove_dir_all(&cache.base_dir).await.ok();
     }
 }
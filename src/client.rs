@@ -1,29 +1,65 @@
-use std::sync::OnceLock;
+use std::{cmp::Reverse, sync::OnceLock, time::Duration};
 
 use async_trait::async_trait;
+use futures::{StreamExt, future::join_all, stream::FuturesUnordered};
 use kugou::KugouFetcher;
 use netease::NeteaseFetcher;
 use qqmusic::QQMusicFetcher;
+use regex::Regex;
 use reqwest::RequestBuilder;
 use serde::de::DeserializeOwned;
+use tokio::time::{Instant, timeout, timeout_at};
 use tracing::{debug, info, warn};
 
+use validate::validate;
+
 use crate::{
-    cache::CacheManager, config::get_config, error::LyricsError, song::SongInfo,
-    utils::normalize_text,
+    cache::CacheManager,
+    config::{SourceOptions, Sources, get_config},
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo, TrackId},
 };
 
 mod kugou;
 mod netease;
 mod ovh;
 mod qqmusic;
+mod validate;
+
+/// 每个来源最多尝试下载的候选数
+const MAX_ATTEMPTS: usize = 3;
 
 /// 歌词抓取器
 #[async_trait]
 trait LyricsFetcher: Send + Sync {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError>;
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError>;
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError>;
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsDoc, LyricsError>;
+    /// 按分数依次下载候选，校验通过后返回选中的候选与歌词
+    async fn fetch_lyric(&self, song: &SongInfo) -> Result<(LyricsItem, LyricsDoc), LyricsError> {
+        let list = self.search_lyric(song).await?;
+        let min_score = get_config().read().unwrap().sources.min_score;
+
+        let mut error = LyricsError::NoLyricsFound;
+        let candidates = rank(list, song)
+            .into_iter()
+            .filter(|item| item.score >= min_score)
+            .take(MAX_ATTEMPTS);
+        for item in candidates {
+            debug!("Get song: {:?} info: {:?}", item, song);
+            match self.download_lyric(&item).await {
+                Ok(doc) => match validate(song, &doc).await {
+                    Ok(()) => return Ok((item, doc)),
+                    Err(e) => error = e,
+                },
+                Err(LyricsError::Instrumental) => return Err(LyricsError::Instrumental),
+                Err(e) => {
+                    warn!("{} download failed: {}", self.source_name(), e);
+                    error = e;
+                }
+            }
+        }
+        Err(error)
+    }
     fn source_name(&self) -> &'static str;
 }
 
@@ -33,29 +69,91 @@ pub struct LyricsItem {
     pub title: String,
     pub artist: String,
     pub album: String,
-    pub params: Vec<(String, String)>,
+    /// 时长（秒），来源未提供时为 None
+    pub duration: Option<f64>,
+    pub id: SourceId,
+    pub kinds: LyricKinds,
+    /// 与当前歌曲的匹配分数，见 [`rank`]
+    pub score: f64,
 }
 
+/// 来源中的歌曲标识，用于下载歌词
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum SourceId {
+    Netease {
+        id: u64,
+    },
+    QQMusic {
+        songmid: String,
+        musicid: u64,
+    },
+    Kugou {
+        id: String,
+        accesskey: String,
+    },
+    /// 按歌手与标题直接查询的来源
+    #[default]
+    None,
+}
+
+/// 来源可提供的歌词类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct LyricKinds {
+    /// 逐行时间
+    pub synced: bool,
+    /// 无时间轴的纯文本
+    pub plain: bool,
+    /// 逐字时间
+    pub word: bool,
+    pub translation: bool,
+}
+
+/// 未设置 `user_agent` 时使用的 User-Agent
+const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/102.0.5005.63 Safari/537.36";
+
 // 公共基础结构
 struct BaseFetcher {
     client: reqwest::Client,
     retries: u8,
+    base_url: Option<String>,
 }
 
 impl Default for BaseFetcher {
     fn default() -> Self {
-        Self::new()
+        Self::new(&SourceOptions::default())
     }
 }
 
 impl BaseFetcher {
-    fn new() -> Self {
+    fn new(options: &SourceOptions) -> Self {
+        let user_agent = options.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let client = reqwest::Client::builder()
+            .user_agent(user_agent)
+            .build()
+            .unwrap_or_else(|e| {
+                warn!("Invalid source options {:?}: {}", options, e);
+                reqwest::Client::new()
+            });
+
         Self {
-            client: reqwest::Client::new(),
-            retries: 3,
+            client,
+            retries: options.retries,
+            base_url: options.base_url.clone(),
         }
     }
 
+    /// 设置了 `base_url` 时替换地址的协议与主机
+    fn url(&self, url: &str) -> String {
+        let Some(base_url) = &self.base_url else {
+            return url.to_string();
+        };
+        let path = url
+            .split_once("://")
+            .and_then(|(_, rest)| rest.find('/').map(|i| &rest[i..]))
+            .unwrap_or("");
+        format!("{}{}", base_url.trim_end_matches('/'), path)
+    }
+
     // 添加重试机制
     async fn fetch_with_retry<T: DeserializeOwned>(
         &self,
@@ -75,6 +173,56 @@ impl BaseFetcher {
             }
         }
     }
+
+    // 非 JSON 接口
+    async fn fetch_text_with_retry(&self, request: RequestBuilder) -> Result<String, LyricsError> {
+        let mut attempt = 0;
+        loop {
+            let response = request.try_clone().unwrap().send().await;
+            debug!("REQUEST: {:?} \n RESPONSE: {:?}", request, response);
+            match response {
+                Ok(res) => return Ok(res.text().await?),
+                Err(_e) if attempt < self.retries => {
+                    tokio::time::sleep(std::time::Duration::from_secs(1 << attempt)).await;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+    }
+}
+
+/// 构造来源的抓取器
+type FetcherBuilder = fn(BaseFetcher) -> Box<dyn LyricsFetcher>;
+
+/// 已支持的来源，键为配置名称，顺序为相同优先级时的请求顺序
+const REGISTRY: [(&str, FetcherBuilder); 3] = [
+    ("kugou", |base| Box::new(KugouFetcher::new(base))),
+    ("netease", |base| Box::new(NeteaseFetcher::new(base))),
+    ("qq", |base| Box::new(QQMusicFetcher::new(base))),
+];
+
+/// 已注册的来源名称，配置中只接受这些名称
+pub(crate) fn source_names() -> impl Iterator<Item = &'static str> {
+    REGISTRY.iter().map(|(name, _)| *name)
+}
+
+impl SourceId {
+    /// 候选所属来源的配置名称，见 [`REGISTRY`]
+    pub fn config_name(&self) -> Option<&'static str> {
+        match self {
+            SourceId::Netease { .. } => Some("netease"),
+            SourceId::QQMusic { .. } => Some("qq"),
+            SourceId::Kugou { .. } => Some("kugou"),
+            SourceId::None => None,
+        }
+    }
+}
+
+/// 按配置创建的来源
+struct Source {
+    fetcher: Box<dyn LyricsFetcher>,
+    timeout: Duration,
 }
 
 /// 初始client
@@ -85,75 +233,130 @@ pub fn get_lyrics_client() -> &'static LyricsClient {
 
 // 统一调用入口
 pub struct LyricsClient {
-    fetchers: Vec<Box<dyn LyricsFetcher>>,
+    sources: Vec<Source>,
     pub cache: CacheManager,
 }
 
 impl LyricsClient {
     fn new() -> Self {
-        let mut fetchers: Vec<Box<dyn LyricsFetcher>> = Vec::new();
-
         let config = &get_config().read().unwrap().sources;
 
-        if config.kugou {
-            fetchers.push(Box::new(KugouFetcher::default()));
-        }
-
-        if config.netease {
-            fetchers.push(Box::new(NeteaseFetcher::default()));
-        }
-
-        if config.qq {
-            fetchers.push(Box::new(QQMusicFetcher::default()));
-        }
+        let mut sources: Vec<_> = REGISTRY
+            .iter()
+            .map(|(name, build)| (name, config.source(name), build))
+            .filter(|(_, options, _)| options.enabled)
+            .collect();
+        // 稳定排序，优先级相同时保持内置顺序
+        sources.sort_by_key(|(_, options, _)| Reverse(options.priority));
+
+        let sources = sources
+            .into_iter()
+            .map(|(name, options, build)| Source {
+                fetcher: build(BaseFetcher::new(&options)),
+                timeout: config.timeout_for(name),
+            })
+            .collect();
 
         Self {
-            fetchers,
+            sources,
             cache: CacheManager::new(),
         }
     }
 
+    /// 同时搜索所有来源，结果按来源顺序合并
     pub async fn get_search(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
-        let mut list = Vec::new();
-
-        for fetcher in &self.fetchers {
-            let mut sl = fetcher.search_lyric(song).await.unwrap_or_default();
-            list.append(&mut sl);
-        }
+        let results = join_all(self.sources.iter().map(|source| async move {
+            match timeout(source.timeout, source.fetcher.search_lyric(song)).await {
+                Ok(result) => result.unwrap_or_default(),
+                Err(_) => {
+                    warn!("{} search timed out", source.fetcher.source_name());
+                    Vec::new()
+                }
+            }
+        }))
+        .await;
 
-        Ok(list)
+        Ok(rank(results.into_iter().flatten().collect(), song))
     }
 
-    pub async fn get_lyrics(&self, song: &SongInfo) -> Result<String, LyricsError> {
+    pub async fn get_lyrics(&self, song: &SongInfo) -> Result<LyricsDoc, LyricsError> {
+        // ClockPlayer 直接使用指定的歌词文件
+        if let TrackId::File(path) = &song.track_id {
+            return Ok(LyricsDoc {
+                lyric: tokio::fs::read_to_string(path).await?,
+                translation: None,
+                romanization: None,
+            });
+        }
+
         if let Some(cached) = self.cache.get(song).await {
             info!("Load local lyric file: {} - {}", song.artist, song.title);
             return Ok(cached);
         }
 
-        for fetcher in &self.fetchers {
-            info!("Trying source: {}", fetcher.source_name());
-            match fetcher.fetch_lyric(song).await {
-                Ok(lyric) => {
-                    //if self.validate_lyric(song, &lyric) {
+        // 同时请求所有来源，每个来源单独超时
+        let deadline = Instant::now() + get_config().read().unwrap().sources.deadline();
+        let mut pending: FuturesUnordered<_> = self
+            .sources
+            .iter()
+            .enumerate()
+            .map(|(i, source)| async move {
+                info!("Trying source: {}", source.fetcher.source_name());
+                let result = timeout(source.timeout, source.fetcher.fetch_lyric(song)).await;
+                (i, result)
+            })
+            .collect();
+
+        // 取分数最高的结果，分数相同时按来源顺序；
+        // 已有足够好的结果、全部返回或到达总等待时间时结束
+        let mut best: Option<(usize, f64, LyricsItem, LyricsDoc)> = None;
+        let mut instrumental = false;
+        while let Ok(Some((i, result))) = timeout_at(deadline, pending.next()).await {
+            let name = self.sources[i].fetcher.source_name();
+            match result {
+                Ok(Ok((item, lyric))) => {
                     info!(
-                        "Successfully fetched {} from {}",
-                        song.title,
-                        fetcher.source_name()
+                        "Successfully fetched {} from {} (score {:.2})",
+                        song.title, name, item.score
                     );
-                    self.cache
-                        .store(song, fetcher.source_name(), &lyric)
-                        .await?;
-                    return Ok(lyric);
-                    // }
+                    let key = rank_key(&item, &get_config().read().unwrap().sources);
+                    if best.as_ref().is_none_or(|(b, best_key, _, _)| {
+                        key > *best_key || (key == *best_key && i < *b)
+                    }) {
+                        best = Some((i, key, item, lyric));
+                    }
+                }
+                Ok(Err(LyricsError::Instrumental)) => {
+                    info!("{} reports instrumental", name);
+                    instrumental = true;
                 }
-                Err(e) => warn!("{} failed: {}", fetcher.source_name(), e),
+                Ok(Err(e)) => warn!("{} failed: {}", name, e),
+                Err(_) => warn!("{} timed out", name),
+            }
+
+            if best
+                .as_ref()
+                .is_some_and(|(_, _, item, _)| item.score >= GOOD_ENOUGH_SCORE)
+            {
+                break;
             }
         }
-        Err(LyricsError::NoLyricsFound)
+
+        if let Some((i, _, _, lyric)) = best {
+            self.cache
+                .store(song, self.sources[i].fetcher.source_name(), &lyric)
+                .await?;
+            return Ok(lyric);
+        }
+
+        match instrumental {
+            true => Err(LyricsError::Instrumental),
+            false => Err(LyricsError::NoLyricsFound),
+        }
     }
 
     pub async fn download(&self, song: &SongInfo, item: &LyricsItem) -> Result<(), LyricsError> {
-        for fetcher in &self.fetchers {
+        for Source { fetcher, .. } in &self.sources {
             if fetcher.source_name() == item.source {
                 match fetcher.download_lyric(item).await {
                     Ok(lyric) => {
@@ -167,6 +370,7 @@ impl LyricsClient {
                             .await?;
                         return Ok(());
                     }
+                    Err(LyricsError::Instrumental) => return Err(LyricsError::Instrumental),
                     Err(e) => warn!("{} failed: {}", fetcher.source_name(), e),
                 }
             }
@@ -174,72 +378,227 @@ impl LyricsClient {
 
         Err(LyricsError::NoLyricsFound)
     }
+}
+
+/// 达到该分数时不再等待其他来源
+const GOOD_ENOUGH_SCORE: f64 = 0.95;
+
+/// 各项匹配的权重，缺少的项不参与计算
+const TITLE_WEIGHT: f64 = 0.5;
+const ARTIST_WEIGHT: f64 = 0.3;
+const ALBUM_WEIGHT: f64 = 0.1;
+const DURATION_WEIGHT: f64 = 0.1;
+/// 时长相差超过该秒数时该项为 0
+const DURATION_TOLERANCE: f64 = 10.0;
+
+/// 现场版、伴奏、翻唱等标记，歌曲标题中没有时扣分
+const VERSION_MARKERS: [&str; 11] = [
+    "live",
+    "karaoke",
+    "instrumental",
+    "cover",
+    "remix",
+    "acoustic",
+    "伴奏",
+    "现场",
+    "翻唱",
+    "纯音乐",
+    "dj",
+];
+const VERSION_PENALTY: f64 = 0.5;
+
+/// 计算候选分数并按分数从高到低排序
+pub fn rank(mut list: Vec<LyricsItem>, song: &SongInfo) -> Vec<LyricsItem> {
+    let config = &get_config().read().unwrap().sources;
+    for item in &mut list {
+        item.score = match_score(&item.title, &item.artist, &item.album, item.duration, song);
+    }
+    // 稳定排序，分数相同时保持来源顺序
+    list.sort_by(|a, b| rank_key(b, config).total_cmp(&rank_key(a, config)));
+    list
+}
+
+/// 排序用的分数：匹配分数加来源先验，先验不计入显示与 `min_score`
+fn rank_key(item: &LyricsItem, config: &Sources) -> f64 {
+    item.score
+        + item
+            .id
+            .config_name()
+            .map_or(0.0, |name| config.prior_for(name))
+}
+
+/// 标题、歌手、专辑与时长的加权相似度，范围 0 ~ 1
+pub(crate) fn match_score(
+    title: &str,
+    artist: &str,
+    album: &str,
+    duration: Option<f64>,
+    song: &SongInfo,
+) -> f64 {
+    let mut parts = vec![(TITLE_WEIGHT, similarity(title, &song.title))];
+
+    if !song.artist.is_empty() {
+        let (a, b) = (match_key(artist), match_key(&song.artist));
+        // 多位歌手时只需包含当前歌手
+        let artist = match !a.is_empty() && !b.is_empty() && (a.contains(&b) || b.contains(&a)) {
+            true => 1.0,
+            false => dice(&a, &b),
+        };
+        parts.push((ARTIST_WEIGHT, artist));
+    }
+    if !song.album.is_empty() && !album.is_empty() {
+        parts.push((ALBUM_WEIGHT, similarity(album, &song.album)));
+    }
+    if let Some(duration) = duration
+        && song.duration > 0.0
+    {
+        let diff = (duration - song.duration).abs();
+        parts.push((DURATION_WEIGHT, (1.0 - diff / DURATION_TOLERANCE).max(0.0)));
+    }
 
-    #[allow(dead_code)]
-    fn validate_lyric(&self, song: &SongInfo, lyric: &str) -> bool {
-        let normalized_lyric = normalize_text(lyric);
-        let has_title = normalized_lyric.contains(&normalize_text(&song.title));
-        let has_artist = normalized_lyric.contains(&normalize_text(&song.artist));
+    let total: f64 = parts.iter().map(|(w, _)| w).sum();
+    let score = parts.iter().map(|(w, v)| w * v).sum::<f64>() / total;
 
-        // 额外检查时长标签（如果有）
-        let has_duration = lyric.contains(&format!("{:0.1}", song.duration));
+    match VERSION_MARKERS
+        .iter()
+        .any(|m| has_marker(title, m) && !has_marker(&song.title, m))
+    {
+        true => score * VERSION_PENALTY,
+        false => score,
+    }
+}
 
-        has_title && has_artist && (song.duration <= 0.0 || has_duration)
+/// 英文标记按整词匹配，避免 alive、adjust 等误判；中文标记没有词边界，直接查找
+fn has_marker(title: &str, marker: &str) -> bool {
+    let title = title.to_lowercase();
+    match marker.is_ascii() {
+        true => title
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| word == marker),
+        false => title.contains(marker),
     }
 }
 
-// 检测两者是否类似
+fn similarity(a: &str, b: &str) -> f64 {
+    dice(&match_key(a), &match_key(b))
+}
 
-pub fn get_first(list: Vec<LyricsItem>, song: &SongInfo) -> Result<LyricsItem, LyricsError> {
-    let list: Vec<LyricsItem> = list
-        .into_iter()
-        .filter(|s| s.title == song.title || s.title.contains(&song.title))
-        .collect();
+/// 去掉 feat. 署名、标点与空白，只保留文字用于比较
+fn match_key(s: &str) -> String {
+    static FEAT: OnceLock<Regex> = OnceLock::new();
+    let feat = FEAT.get_or_init(|| {
+        Regex::new(r"(?i)[(\[（]?\s*\b(feat\.?|ft\.|featuring)\s[^)\]）]*[)\]）]?").unwrap()
+    });
+    feat.replace_all(s, "")
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
 
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
+/// 字符二元组的 Dice 系数
+fn dice(a: &str, b: &str) -> f64 {
+    if a == b {
+        return 1.0;
     }
+    // 单字标题没有二元组，退回包含判断
+    if a.chars().count() < 2 || b.chars().count() < 2 {
+        return match !a.is_empty() && !b.is_empty() && (a.contains(b) || b.contains(a)) {
+            true => 1.0,
+            false => 0.0,
+        };
+    }
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+
+    let mut bigrams: Vec<(char, char)> = b.windows(2).map(|w| (w[0], w[1])).collect();
+    let mut common = 0;
+    for w in a.windows(2) {
+        if let Some(i) = bigrams.iter().position(|g| *g == (w[0], w[1])) {
+            bigrams.swap_remove(i);
+            common += 1;
+        }
+    }
+    2.0 * common as f64 / (a.len() + b.len() - 2) as f64
+}
 
-    let list: Vec<LyricsItem> = if !song.artist.is_empty() {
-        list.into_iter()
-            .filter(|s| {
-                if song.artist.is_empty() {
-                    true
-                } else {
-                    let a = s.artist.to_lowercase();
-                    let b = song.artist.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .collect()
-    } else {
-        list
-    };
-
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
-    }
-
-    let list: Vec<LyricsItem> = if !song.album.is_empty() {
-        list.into_iter()
-            .filter(|s| {
-                if song.album.is_empty() {
-                    true
-                } else {
-                    let a = s.album.to_lowercase();
-                    let b = song.album.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .collect()
-    } else {
-        list
-    };
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SourceSetting;
+
+    fn song(title: &str) -> SongInfo {
+        SongInfo {
+            title: title.to_string(),
+            ..Default::default()
+        }
+    }
 
-    if list.is_empty() {
-        return Err(LyricsError::NoLyricsFound);
+    fn item(source: &str, id: SourceId, title: &str) -> LyricsItem {
+        LyricsItem {
+            source: source.to_string(),
+            title: title.to_string(),
+            artist: String::new(),
+            album: String::new(),
+            duration: None,
+            id,
+            kinds: LyricKinds::default(),
+            score: 0.0,
+        }
     }
 
-    let first = list.first().ok_or(LyricsError::NoLyricsFound)?;
-    Ok(first.clone())
+    #[test]
+    fn version_markers_match_whole_words() {
+        assert!(has_marker("Song (Live)", "live"));
+        assert!(has_marker("Song - DJ版", "dj"));
+        assert!(has_marker("歌 (伴奏)", "伴奏"));
+        assert!(!has_marker("Alive", "live"));
+        assert!(!has_marker("Adjust", "dj"));
+        assert!(!has_marker("Discover", "cover"));
+
+        let alive = match_score("Alive", "", "", None, &song("Alive"));
+        assert_eq!(alive, 1.0);
+        let live = match_score("Alive (Live)", "", "", None, &song("Alive"));
+        assert!(live < alive * VERSION_PENALTY + 1e-9);
+    }
+
+    #[test]
+    fn short_titles_fall_back_to_containment() {
+        assert_eq!(dice("雨", "雨"), 1.0);
+        assert_eq!(dice("雨", "雨天"), 1.0);
+        assert_eq!(dice("雨天", "雨"), 1.0);
+        assert_eq!(dice("雨", "风"), 0.0);
+        assert_eq!(dice("", "雨"), 0.0);
+        assert!(match_score("雨", "", "", None, &song("雨 ")) > 0.9);
+    }
+
+    #[test]
+    fn prior_orders_candidates_without_changing_scores() {
+        get_config().write().unwrap().sources.fetchers.insert(
+            "netease".to_string(),
+            SourceSetting::Options(SourceOptions {
+                prior: 0.5,
+                ..Default::default()
+            }),
+        );
+
+        let kugou = SourceId::Kugou {
+            id: "1".to_string(),
+            accesskey: String::new(),
+        };
+        let list = rank(
+            vec![
+                item("Kugou", kugou, "Song"),
+                item("Netease", SourceId::Netease { id: 1 }, "Song"),
+                item("Netease", SourceId::Netease { id: 2 }, "Other"),
+            ],
+            &song("Song"),
+        );
+        assert_eq!(list[0].id, SourceId::Netease { id: 1 });
+        assert_eq!(list[1].source, "Kugou");
+        // 先验只影响排序，分数仍在 0 ~ 1
+        assert_eq!(list[0].score, 1.0);
+        assert_eq!(list[1].score, 1.0);
+        assert!(list.iter().all(|item| (0.0..=1.0).contains(&item.score)));
+    }
 }
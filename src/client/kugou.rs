@@ -1,10 +1,17 @@
+use std::io::Read;
+
 use async_trait::async_trait;
 use base64::{Engine, prelude::BASE64_STANDARD};
+use flate2::read::ZlibDecoder;
 use serde::Deserialize;
-use tracing::debug;
+use tracing::{debug, warn};
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricKinds, LyricsFetcher, LyricsItem, SourceId};
+use crate::{
+    client::match_score,
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo, enhanced_lrc_line},
+};
 
 #[derive(Debug, Deserialize)]
 struct SearchResponse {
@@ -25,6 +32,9 @@ struct Song {
     singername: String,
     // songname: String,
     songname_original: String,
+    /// 秒
+    #[serde(default)]
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,6 +48,9 @@ struct Candidate {
     download_id: String,
     singer: String,
     song: String,
+    /// 毫秒
+    #[serde(default)]
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -45,13 +58,29 @@ struct LyricData {
     content: String,
 }
 
+/// KRC 解密密钥
+const KRC_KEY: [u8; 16] = [
+    0x40, 0x47, 0x61, 0x77, 0x5e, 0x32, 0x74, 0x47, 0x51, 0x36, 0x31, 0x2d, 0xce, 0xd2, 0x6e, 0x69,
+];
+
+/// 提供 LRC 与逐字 KRC
+const KINDS: LyricKinds = LyricKinds {
+    synced: true,
+    plain: false,
+    word: true,
+    translation: false,
+};
+
 // Kugou音乐实现
-#[derive(Default)]
 pub(super) struct KugouFetcher {
     base: BaseFetcher,
 }
 
 impl KugouFetcher {
+    pub(super) fn new(base: BaseFetcher) -> Self {
+        Self { base }
+    }
+
     // 酷狗歌词解密函数
     fn decode_lyric(&self, encrypted: &str) -> Result<String, LyricsError> {
         let bytes = BASE64_STANDARD.decode(encrypted)?;
@@ -61,6 +90,113 @@ impl KugouFetcher {
         }
         Ok(re)
     }
+
+    // KRC 解密后转换为增强 LRC
+    fn decode_krc(&self, encrypted: &str) -> Result<String, LyricsError> {
+        let krc = decrypt_krc(encrypted)?;
+        let lrc = krc_to_lrc(krc.trim_start_matches('\u{feff}'));
+        if lrc.is_empty() {
+            return Err(LyricsError::NoLyricsFound);
+        }
+        Ok(lrc)
+    }
+
+    async fn download(&self, item: &LyricsItem, fmt: &str) -> Result<LyricData, LyricsError> {
+        let SourceId::Kugou { id, accesskey } = &item.id else {
+            return Err(LyricsError::NoLyricsFound);
+        };
+
+        let lyric_download_url = "http://lyrics.kugou.com/download";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(lyric_download_url))
+            .query(&[
+                ("id", id.as_str()),
+                ("accesskey", accesskey.as_str()),
+                ("ver", "1"),
+                ("client", "pc"),
+                ("fmt", fmt),
+                ("charset", "utf8"),
+            ]);
+
+        let data: LyricData = self.base.fetch_with_retry(request).await?;
+        debug!("lyric: {:?}", data);
+        Ok(data)
+    }
+}
+
+/// KRC 解密: base64 -> 去掉 `krc1` 头 -> XOR -> zlib
+fn decrypt_krc(encrypted: &str) -> Result<String, LyricsError> {
+    let bytes = BASE64_STANDARD.decode(encrypted)?;
+    let body = bytes
+        .strip_prefix(b"krc1")
+        .ok_or(LyricsError::LyricsDecodeError)?;
+
+    let decrypted: Vec<u8> = body
+        .iter()
+        .enumerate()
+        .map(|(i, b)| b ^ KRC_KEY[i % KRC_KEY.len()])
+        .collect();
+
+    let mut krc = String::new();
+    ZlibDecoder::new(decrypted.as_slice())
+        .read_to_string(&mut krc)
+        .map_err(|_| LyricsError::LyricsDecodeError)?;
+    Ok(krc)
+}
+
+/// KRC 转换为增强 LRC
+///
+/// 行格式 `[start,duration]<offset,duration,0>word...`，字的时间相对于行开始。
+fn krc_to_lrc(krc: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in krc.lines() {
+        let line = line.trim();
+        let Some((head, body)) = line.strip_prefix('[').and_then(|l| l.split_once(']')) else {
+            continue;
+        };
+
+        // 头部标签，`[language:]` 等非 LRC 标签直接丢弃
+        if let Some((key, value)) = head.split_once(':') {
+            match key {
+                "ti" | "ar" | "al" | "by" | "offset" => lines.push(format!("[{key}:{value}]")),
+                "total" => {
+                    if let Ok(ms) = value.parse::<u64>() {
+                        lines.push(format!("[length:{:02}:{:02}]", ms / 60_000, ms / 1000 % 60));
+                    }
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let Some(start) = head.split(',').next().and_then(|s| s.parse::<u64>().ok()) else {
+            continue;
+        };
+
+        let mut words = Vec::new();
+        let mut rest = body;
+        while let Some(tag) = rest.strip_prefix('<') {
+            let Some((timing, after)) = tag.split_once('>') else {
+                break;
+            };
+            let text_end = after.find('<').unwrap_or(after.len());
+            let mut timing = timing.split(',').map(|s| s.parse::<u64>().unwrap_or(0));
+            let offset = timing.next().unwrap_or(0);
+            let duration = timing.next().unwrap_or(0);
+            // 空字只占位，间隔由下一个字的开始时间体现
+            if text_end > 0 {
+                words.push((start + offset, duration, after[..text_end].to_string()));
+            }
+            rest = &after[text_end..];
+        }
+
+        lines.push(enhanced_lrc_line(start, &words));
+    }
+
+    lines.join("\n")
 }
 
 #[async_trait]
@@ -68,7 +204,7 @@ impl LyricsFetcher for KugouFetcher {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         // 1. 搜索歌曲
         let search_url = "http://mobilecdn.kugou.com/api/v3/search/song";
-        let request = self.base.client.get(search_url).query(&[
+        let request = self.base.client.get(self.base.url(search_url)).query(&[
             (
                 "keyword",
                 format!("{} {}", song.title, song.artist).as_str(),
@@ -84,26 +220,17 @@ impl LyricsFetcher for KugouFetcher {
             .data
             .info
             .into_iter()
-            .filter(|s| {
-                s.songname_original == song.title || s.songname_original.contains(&song.title)
-            })
-            .filter(|s| {
-                if song.artist.is_empty() {
-                    true
-                } else {
-                    let a = s.singername.to_lowercase();
-                    let b = song.artist.to_lowercase();
-                    a == b || a.contains(&b)
-                }
-            })
-            .find(|s| {
-                if song.album.is_empty() {
-                    true
-                } else {
-                    let a = s.album_name.to_lowercase();
-                    let b = song.album.to_lowercase();
-                    a == b || a.contains(&b)
-                }
+            .max_by(|a, b| {
+                let score = |s: &Song| {
+                    match_score(
+                        &s.songname_original,
+                        &s.singername,
+                        &s.album_name,
+                        s.duration.map(|secs| secs as f64),
+                        song,
+                    )
+                };
+                score(a).total_cmp(&score(b))
             })
             .ok_or(LyricsError::NoLyricsFound)?;
 
@@ -111,18 +238,13 @@ impl LyricsFetcher for KugouFetcher {
 
         // 2. 获取歌词
         let lyric_url = "http://krcs.kugou.com/search";
-        let request = self
-            .base
-            .client
-            .get(lyric_url)
-            .query(&[
-                ("hash", search.hash.as_str()),
-                ("album_id", search.album_id.as_str()),
-                ("ver", "1"),
-                ("client", "pc"),
-                ("man", "yes"),
-            ])
-            .header("User-Agent", "Mozilla/5.0");
+        let request = self.base.client.get(self.base.url(lyric_url)).query(&[
+            ("hash", search.hash.as_str()),
+            ("album_id", search.album_id.as_str()),
+            ("ver", "1"),
+            ("client", "pc"),
+            ("man", "yes"),
+        ]);
 
         let data: LyricResponse = self.base.fetch_with_retry(request).await?;
         debug!("lyric list: {:?}", data);
@@ -135,17 +257,24 @@ impl LyricsFetcher for KugouFetcher {
                 let title = s.song;
                 let artist = s.singer;
                 let album = search.album_name.clone();
-                let params = vec![
-                    ("accesskey".to_string(), s.accesskey),
-                    ("id".to_string(), s.download_id),
-                ];
+                // 候选没有时长时使用搜索到的歌曲时长
+                let duration = s
+                    .duration
+                    .map(|ms| ms as f64 / 1000.0)
+                    .or(search.duration.map(|secs| secs as f64));
 
                 LyricsItem {
                     source,
                     title,
                     artist,
                     album,
-                    params,
+                    duration,
+                    id: SourceId::Kugou {
+                        id: s.download_id,
+                        accesskey: s.accesskey,
+                    },
+                    kinds: KINDS,
+                    score: 0.0,
                 }
             })
             .collect();
@@ -159,40 +288,106 @@ impl LyricsFetcher for KugouFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
-        let mut params = item.params.clone();
-        params.append(&mut vec![
-            ("ver".to_string(), "1".to_string()),
-            ("client".to_string(), "pc".to_string()),
-            ("fmt".to_string(), "lrc".to_string()),
-            ("charset".to_string(), "utf8".to_string()),
-        ]);
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsDoc, LyricsError> {
+        // 3. 下载, 优先逐字的 KRC，失败时回退到 LRC
+        match self.download(item, "krc").await {
+            Ok(data) => match self.decode_krc(&data.content) {
+                Ok(lrc) => return Ok(LyricsDoc::new(lrc)),
+                Err(e) => warn!("Kugou krc decode failed: {}", e),
+            },
+            Err(e) => warn!("Kugou krc download failed: {}", e),
+        }
 
-        // 3. 下载
-        let lyric_download_url = "http://lyrics.kugou.com/download";
-        let request = self
-            .base
-            .client
-            .get(lyric_download_url)
-            .query(&params)
-            .header("User-Agent", "Mozilla/5.0");
+        let data = self.download(item, "lrc").await?;
+        let decoded = self.decode_lyric(&data.content)?;
+        Ok(LyricsDoc::new(decoded))
+    }
 
-        let data: LyricData = self.base.fetch_with_retry(request).await?;
-        debug!("lyric: {:?}", data);
+    fn source_name(&self) -> &'static str {
+        "Kugou"
+    }
+}
 
-        let decoded = self.decode_lyric(&data.content)?;
-        Ok(decoded)
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use flate2::{Compression, write::ZlibEncoder};
+
+    use super::*;
+    use crate::client::BaseFetcher;
+
+    const KRC: &str = "\u{feff}[id:$00000000]\n[ar:周杰伦]\n[ti:晴天]\n[language:eyJjb250ZW50IjpbXX0=]\n[total:269000]\n[offset:0]\n[1000,2000]<0,500,0>故<500,500,0>事<1000,1000,0>的\n[3500,1200]<0,700,0>Hello <900,300,0>world";
+
+    /// 按酷狗的格式打包: zlib -> XOR -> `krc1` 头 -> base64
+    fn encrypt_krc(krc: &str) -> String {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(krc.as_bytes()).unwrap();
+        let body: Vec<u8> = encoder
+            .finish()
+            .unwrap()
+            .iter()
+            .enumerate()
+            .map(|(i, b)| b ^ KRC_KEY[i % KRC_KEY.len()])
+            .collect();
+        BASE64_STANDARD.encode([b"krc1".as_slice(), &body].concat())
     }
 
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
-        debug!("kugou start ");
-        let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
-        debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+    #[test]
+    fn krc_converts_to_enhanced_lrc() {
+        assert_eq!(
+            krc_to_lrc(KRC.trim_start_matches('\u{feff}')),
+            "[ar:周杰伦]\n[ti:晴天]\n[length:04:29]\n[offset:0]\n\
+             [00:01.00]<00:01.00>故<00:01.50>事<00:02.00>的<00:03.00>\n\
+             [00:03.50]<00:03.50>Hello <00:04.20><00:04.40>world<00:04.70>"
+        );
     }
 
-    fn source_name(&self) -> &'static str {
-        "Kugou"
+    #[test]
+    fn decode_krc_fixture() {
+        let fetcher = KugouFetcher::new(BaseFetcher::default());
+        let lrc = fetcher.decode_krc(&encrypt_krc(KRC)).unwrap();
+        assert_eq!(lrc, krc_to_lrc(KRC.trim_start_matches('\u{feff}')));
+    }
+
+    /// 下载接口 `fmt=krc` 的完整响应，离线按相同格式生成，待替换为实际抓包
+    const KRC_RESPONSE: &str = include_str!("fixtures/kugou_krc.json");
+
+    #[test]
+    fn decode_krc_response() {
+        let data: LyricData = serde_json::from_str(KRC_RESPONSE).unwrap();
+        let krc = decrypt_krc(&data.content).unwrap();
+        let lines: Vec<&str> = krc.lines().map(str::trim).collect();
+        assert_eq!(lines[0], "\u{feff}[id:$00000000]");
+        assert_eq!(
+            &lines[11..],
+            [
+                "[1620,3010]<0,400,0>晚<400,350,0>风<750,600,0>吹<1350,510,0>过<1860,450,0>旧<2310,700,0>街",
+                "[5030,2280]<0,520,0>Hello <520,0,0><700,1580,0>world",
+            ]
+        );
+
+        let fetcher = KugouFetcher::new(BaseFetcher::default());
+        assert_eq!(
+            fetcher.decode_krc(&data.content).unwrap(),
+            "[ar:测试歌手]\n[ti:晚风]\n[by:]\n[al:]\n[length:03:35]\n[offset:0]\n\
+             [00:01.62]<00:01.62>晚<00:02.02>风<00:02.37>吹<00:02.97>过<00:03.48>旧<00:03.93>街<00:04.63>\n\
+             [00:05.03]<00:05.03>Hello <00:05.55><00:05.73>world<00:07.31>"
+        );
+    }
+
+    #[test]
+    fn decode_krc_rejects_bad_input() {
+        let fetcher = KugouFetcher::new(BaseFetcher::default());
+        assert!(
+            fetcher
+                .decode_krc(&BASE64_STANDARD.encode("lrc1abc"))
+                .is_err()
+        );
+        assert!(
+            fetcher
+                .decode_krc(&BASE64_STANDARD.encode("krc1abc"))
+                .is_err()
+        );
     }
 }
@@ -2,8 +2,11 @@ use async_trait::async_trait;
 use serde::Deserialize;
 use tracing::debug;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricKinds, LyricsFetcher, LyricsItem, SourceId};
+use crate::{
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo, enhanced_lrc_line, format_timestamp},
+};
 
 #[derive(Debug, Deserialize)]
 struct Response {
@@ -23,7 +26,9 @@ struct Song {
     name: String,
     artists: Vec<Artist>,
     album: Album,
-    // duration: u64,
+    /// 毫秒
+    #[serde(default)]
+    duration: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -40,8 +45,23 @@ struct Album {
 }
 
 #[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
 struct LyricData {
-    lrc: LrcData,
+    lrc: Option<LrcData>,
+    /// 逐字歌词
+    yrc: Option<LrcData>,
+    /// 翻译
+    tlyric: Option<LrcData>,
+    /// 罗马音
+    romalrc: Option<LrcData>,
+    /// 无歌词
+    #[serde(default)]
+    nolyric: bool,
+    /// 未收录
+    #[serde(default)]
+    uncollected: bool,
+    #[serde(default)]
+    pure_music: bool,
 }
 
 #[derive(Debug, Deserialize)]
@@ -50,12 +70,101 @@ struct LrcData {
 }
 
 // 网易云音乐实现
-#[derive(Default)]
 pub(super) struct NeteaseFetcher {
     base: BaseFetcher,
 }
 
-impl NeteaseFetcher {}
+impl NeteaseFetcher {
+    pub(super) fn new(base: BaseFetcher) -> Self {
+        Self { base }
+    }
+}
+
+/// 纯音乐的歌词中只有这一句提示
+const PURE_MUSIC_HINT: &str = "纯音乐，请欣赏";
+
+/// 提供 LRC、逐字 YRC 与翻译
+const KINDS: LyricKinds = LyricKinds {
+    synced: true,
+    plain: false,
+    word: true,
+    translation: true,
+};
+
+/// YRC 头部信息行 `{"t":0,"c":[{"tx":"作词: "},{"tx":"xxx"}]}`
+#[derive(Debug, Deserialize)]
+struct YrcInfo {
+    t: u64,
+    c: Vec<YrcText>,
+}
+
+#[derive(Debug, Deserialize)]
+struct YrcText {
+    tx: String,
+}
+
+/// YRC 转换为增强 LRC
+///
+/// 行格式 `[start,duration](start,duration,0)word...`，字的时间为绝对时间且位于文字之前。
+fn yrc_to_lrc(yrc: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in yrc.lines() {
+        let line = line.trim();
+
+        if line.starts_with('{') {
+            if let Ok(info) = serde_json::from_str::<YrcInfo>(line) {
+                let text: String = info.c.into_iter().map(|c| c.tx).collect();
+                lines.push(format!("[{}]{}", format_timestamp(info.t), text));
+            }
+            continue;
+        }
+
+        let Some((head, body)) = line.strip_prefix('[').and_then(|l| l.split_once(']')) else {
+            continue;
+        };
+        let Some(start) = head.split(',').next().and_then(|s| s.parse::<u64>().ok()) else {
+            continue;
+        };
+
+        let mut words: Vec<(u64, u64, String)> = Vec::new();
+        let mut rest = body;
+        while !rest.is_empty() {
+            let timing = rest
+                .strip_prefix('(')
+                .and_then(|r| r.split_once(')'))
+                .and_then(|(inner, after)| {
+                    let mut parts = inner.split(',').map(|s| s.parse::<u64>().ok());
+                    Some((parts.next()??, parts.next()??, after))
+                });
+
+            match timing {
+                Some((word_start, duration, after)) => {
+                    words.push((word_start, duration, String::new()));
+                    rest = after;
+                }
+                None => {
+                    // 读取到下一个 `(` 之前的文字，歌词本身带有括号时原样保留
+                    let next = rest
+                        .char_indices()
+                        .skip(1)
+                        .find(|(_, c)| *c == '(')
+                        .map(|(i, _)| i)
+                        .unwrap_or(rest.len());
+                    match words.last_mut() {
+                        Some(word) => word.2.push_str(&rest[..next]),
+                        None => words.push((start, 0, rest[..next].to_string())),
+                    }
+                    rest = &rest[next..];
+                }
+            }
+        }
+
+        lines.push(enhanced_lrc_line(start, &words));
+    }
+
+    lines.join("\n")
+}
 
 #[async_trait]
 impl LyricsFetcher for NeteaseFetcher {
@@ -63,7 +172,7 @@ impl LyricsFetcher for NeteaseFetcher {
         let search_url = "https://music.163.com/api/search/get/";
         // TODO 分词处理
 
-        let request = self.base.client.get(search_url).query(&[
+        let request = self.base.client.get(self.base.url(search_url)).query(&[
             ("s", format!("{} {}", song.title, song.artist).as_str()),
             ("type", "1"),
             ("limit", "10"), // song_id 1, album_id 10 playlist_id 1000
@@ -90,17 +199,17 @@ impl LyricsFetcher for NeteaseFetcher {
                 //     full
                 // });
                 let album = s.album.name;
-                let params = vec![
-                    ("id".to_string(), s.id.to_string()),
-                    ("lv".to_string(), "1".to_string()),
-                ];
+                let duration = s.duration.map(|ms| ms as f64 / 1000.0);
 
                 LyricsItem {
                     source,
                     title,
                     artist,
                     album,
-                    params,
+                    duration,
+                    id: SourceId::Netease { id: s.id },
+                    kinds: KINDS,
+                    score: 0.0,
                 }
             })
             .collect();
@@ -114,20 +223,55 @@ impl LyricsFetcher for NeteaseFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
-        let lyric_url = "https://music.163.com/api/song/lyric";
-        let request = self.base.client.get(lyric_url).query(&item.params);
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsDoc, LyricsError> {
+        let SourceId::Netease { id } = item.id else {
+            return Err(LyricsError::NoLyricsFound);
+        };
+
+        let lyric_url = "https://music.163.com/api/song/lyric/v1";
+        let request = self.base.client.get(self.base.url(lyric_url)).query(&[
+            ("id", id.to_string().as_str()),
+            ("lv", "1"),
+            ("yv", "1"),
+            ("tv", "-1"),
+            ("rv", "-1"),
+        ]);
         let data: LyricData = self.base.fetch_with_retry(request).await?;
         debug!("Get lyric: {:?}", data);
-        Ok(data.lrc.lyric)
-    }
 
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
-        debug!("Netease song: {:?}", song);
-        let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
-        debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+        if data.pure_music || data.nolyric {
+            return Err(LyricsError::Instrumental);
+        }
+        if data.uncollected {
+            return Err(LyricsError::NoLyricsFound);
+        }
+
+        let lrc = data.lrc.map(|l| l.lyric).unwrap_or_default();
+        if lrc.contains(PURE_MUSIC_HINT) {
+            return Err(LyricsError::Instrumental);
+        }
+
+        let translation = data
+            .tlyric
+            .map(|t| t.lyric)
+            .filter(|t| !t.trim().is_empty());
+        let romanization = data
+            .romalrc
+            .map(|r| r.lyric)
+            .filter(|r| !r.trim().is_empty());
+
+        // 优先使用逐字歌词
+        let lyric = match data.yrc {
+            Some(yrc) if !yrc.lyric.trim().is_empty() => yrc_to_lrc(&yrc.lyric),
+            _ if lrc.trim().is_empty() => return Err(LyricsError::NoLyricsFound),
+            _ => lrc,
+        };
+
+        Ok(LyricsDoc {
+            lyric,
+            translation,
+            romanization,
+        })
     }
 
     fn source_name(&self) -> &'static str {
@@ -1,8 +1,11 @@
 use async_trait::async_trait;
 use serde_json::Value;
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{error::LyricsError, song::SongInfo};
+use super::{BaseFetcher, LyricKinds, LyricsFetcher, LyricsItem, SourceId};
+use crate::{
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo},
+};
 
 // Spotify音乐实现
 #[allow(dead_code)]
@@ -16,10 +19,10 @@ impl LyricsFetcher for OvhFetcher {
     async fn search_lyric(&self, _song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         Err(LyricsError::NoLyricsFound)
     }
-    async fn download_lyric(&self, _item: &LyricsItem) -> Result<String, LyricsError> {
+    async fn download_lyric(&self, _item: &LyricsItem) -> Result<LyricsDoc, LyricsError> {
         Err(LyricsError::NoLyricsFound)
     }
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
+    async fn fetch_lyric(&self, song: &SongInfo) -> Result<(LyricsItem, LyricsDoc), LyricsError> {
         // 假设使用的第三方Spotify歌词API如下（实际应使用真实的API）
         let ovh_api = "https://api.lyrics.ovh/v1";
 
@@ -45,8 +48,23 @@ impl LyricsFetcher for OvhFetcher {
             return Err(LyricsError::NoLyricsFound);
         }
 
+        // 按歌手与标题直接查询，没有候选列表
+        let item = LyricsItem {
+            source: self.source_name().into(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            duration: None,
+            id: SourceId::None,
+            kinds: LyricKinds {
+                plain: true,
+                ..Default::default()
+            },
+            score: 0.0,
+        };
+
         // 假设第三方API返回的歌词不需要解码或特殊处理
-        Ok(lyrics.to_string())
+        Ok((item, LyricsDoc::new(lyrics.to_string())))
     }
 
     fn source_name(&self) -> &'static str {
@@ -1,10 +1,18 @@
+use std::io::Read;
+
 use async_trait::async_trait;
 use base64::{Engine, prelude::BASE64_STANDARD};
+use flate2::read::ZlibDecoder;
 use serde::Deserialize;
-use tracing::debug;
+use tracing::{debug, warn};
+
+use super::{BaseFetcher, LyricKinds, LyricsFetcher, LyricsItem, SourceId};
+use crate::{
+    error::LyricsError,
+    song::{LyricsDoc, SongInfo, enhanced_lrc_line},
+};
 
-use super::{BaseFetcher, LyricsFetcher, LyricsItem};
-use crate::{client::get_first, error::LyricsError, song::SongInfo};
+mod des;
 
 #[derive(Debug, Deserialize)]
 struct Response {
@@ -23,10 +31,14 @@ struct SongPack {
 }
 #[derive(Debug, Deserialize)]
 struct Song {
+    songid: u64,
     songmid: String,
     songname: String,
     singer: Vec<Artist>,
     albumname: String,
+    /// 秒
+    #[serde(default)]
+    interval: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -38,36 +50,231 @@ struct Artist {
 #[derive(Debug, Deserialize)]
 struct LyricsData {
     lyric: String,
+    /// 翻译
+    #[serde(default)]
+    trans: String,
 }
 
+/// 提供 LRC、逐字 QRC 与翻译
+const KINDS: LyricKinds = LyricKinds {
+    synced: true,
+    plain: false,
+    word: true,
+    translation: true,
+};
+
 // QQ音乐实现
-#[derive(Default)]
 pub(super) struct QQMusicFetcher {
     base: BaseFetcher,
 }
 
+impl QQMusicFetcher {
+    pub(super) fn new(base: BaseFetcher) -> Self {
+        Self { base }
+    }
+
+    // 逐字 QRC 歌词
+    async fn download_qrc(&self, musicid: u64) -> Result<LyricsDoc, LyricsError> {
+        let lyrics_url = "https://c.y.qq.com/qqmusic/fcgi-bin/lyric_download.fcg";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(lyrics_url))
+            .query(&[
+                ("musicid", musicid.to_string().as_str()),
+                ("version", "15"),
+                ("miniversion", "82"),
+                ("lrctype", "4"),
+            ])
+            .header("Referer", "https://y.qq.com/n/ryqq/player");
+
+        let data = self.base.fetch_text_with_retry(request).await?;
+        let encrypted = xml_cdata(&data, "content").ok_or(LyricsError::NoLyricsFound)?;
+        let lyric = decode_qrc(encrypted)?;
+
+        // 翻译为加密的普通 LRC
+        let translation = xml_cdata(&data, "contentts")
+            .and_then(|t| decrypt_qrc(t).ok())
+            .filter(|t| !t.trim().is_empty());
+
+        Ok(LyricsDoc {
+            lyric,
+            translation,
+            ..Default::default()
+        })
+    }
+
+    // 普通 LRC 歌词
+    async fn download_lrc(&self, songmid: &str) -> Result<LyricsDoc, LyricsError> {
+        let lyrics_url = "https://c.y.qq.com/lyric/fcgi-bin/fcg_query_lyric_new.fcg";
+        let request = self
+            .base
+            .client
+            .get(self.base.url(lyrics_url))
+            .query(&[("songmid", songmid), ("format", "json"), ("g_tk", "5381")])
+            .header("Referer", "https://y.qq.com/n/ryqq/player")
+            .header("Origin", "https://y.qq.com");
+
+        let data: LyricsData = self.base.fetch_with_retry(request).await?;
+
+        // 处理Base64解码
+        let decoded = BASE64_STANDARD
+            .decode(data.lyric)
+            .map_err(|_| LyricsError::LyricsDecodeError)?;
+
+        let re = String::from_utf8(decoded).map_err(|_| LyricsError::LyricsDecodeError)?;
+        if re.is_empty() {
+            return Err(LyricsError::NoLyricsFound);
+        }
+
+        let translation = BASE64_STANDARD
+            .decode(data.trans)
+            .ok()
+            .and_then(|t| String::from_utf8(t).ok())
+            .filter(|t| !t.trim().is_empty());
+
+        Ok(LyricsDoc {
+            lyric: re,
+            translation,
+            ..Default::default()
+        })
+    }
+}
+
+/// 读取 `<tag><![CDATA[...]]></tag>` 的内容
+fn xml_cdata<'a>(xml: &'a str, tag: &str) -> Option<&'a str> {
+    let start = xml.find(&format!("<{tag}>"))? + tag.len() + 2;
+    let end = start + xml[start..].find(&format!("</{tag}>"))?;
+    let content = xml[start..end].trim();
+    let content = content
+        .strip_prefix("<![CDATA[")
+        .and_then(|c| c.strip_suffix("]]>"))
+        .unwrap_or(content)
+        .trim();
+    (!content.is_empty()).then_some(content)
+}
+
+fn decode_hex(s: &str) -> Option<Vec<u8>> {
+    if !s.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(s.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// QRC 解密: hex -> 三重 DES -> zlib
+fn decrypt_qrc(encrypted: &str) -> Result<String, LyricsError> {
+    let mut bytes = decode_hex(encrypted).ok_or(LyricsError::LyricsDecodeError)?;
+    des::triple_des_decrypt(&mut bytes, des::QRC_KEY);
+
+    let mut text = String::new();
+    ZlibDecoder::new(bytes.as_slice())
+        .read_to_string(&mut text)
+        .map_err(|_| LyricsError::LyricsDecodeError)?;
+    Ok(text)
+}
+
+/// 解密 QRC 并取出 XML 中的 `LyricContent`
+fn decode_qrc(encrypted: &str) -> Result<String, LyricsError> {
+    let xml = decrypt_qrc(encrypted)?;
+
+    // QQ 返回的 XML 并不总是合法（歌词中的引号不转义），直接截取属性内容
+    let start = xml
+        .find("LyricContent=\"")
+        .ok_or(LyricsError::LyricsDecodeError)?
+        + "LyricContent=\"".len();
+    let end = xml[start..]
+        .rfind("\"/>")
+        .map(|i| start + i)
+        .ok_or(LyricsError::LyricsDecodeError)?;
+    let content = xml[start..end]
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&");
+
+    let lrc = qrc_to_lrc(&content);
+    if lrc.is_empty() {
+        return Err(LyricsError::NoLyricsFound);
+    }
+    Ok(lrc)
+}
+
+/// QRC 转换为增强 LRC
+///
+/// 行格式 `[start,duration]word(start,duration)...`，字的时间为绝对时间且位于文字之后。
+fn qrc_to_lrc(qrc: &str) -> String {
+    let mut lines = Vec::new();
+
+    for line in qrc.lines() {
+        let line = line.trim();
+        let Some((head, body)) = line.strip_prefix('[').and_then(|l| l.split_once(']')) else {
+            continue;
+        };
+
+        if let Some((key, value)) = head.split_once(':') {
+            if matches!(key, "ti" | "ar" | "al" | "by" | "offset") {
+                lines.push(format!("[{key}:{value}]"));
+            }
+            continue;
+        }
+
+        let Some(start) = head.split(',').next().and_then(|s| s.parse::<u64>().ok()) else {
+            continue;
+        };
+
+        let mut words = Vec::new();
+        let mut text = String::new();
+        let mut rest = body;
+        while let Some(open) = rest.find('(') {
+            let timing = rest[open + 1..].split_once(')').and_then(|(inner, after)| {
+                let (s, d) = inner.split_once(',')?;
+                Some((s.parse::<u64>().ok()?, d.parse::<u64>().ok()?, after))
+            });
+
+            match timing {
+                Some((word_start, duration, after)) => {
+                    text.push_str(&rest[..open]);
+                    words.push((word_start, duration, std::mem::take(&mut text)));
+                    rest = after;
+                }
+                // 歌词本身带有括号
+                None => {
+                    text.push_str(&rest[..=open]);
+                    rest = &rest[open + 1..];
+                }
+            }
+        }
+
+        lines.push(enhanced_lrc_line(start, &words));
+    }
+
+    lines.join("\n")
+}
+
 #[async_trait]
 impl LyricsFetcher for QQMusicFetcher {
     async fn search_lyric(&self, song: &SongInfo) -> Result<Vec<LyricsItem>, LyricsError> {
         // 1. 搜索歌曲
         let search_url = "https://c.y.qq.com/soso/fcgi-bin/client_search_cp";
-        let request= self
+        let request = self
             .base
             .client
-            .get(search_url)
+            .get(self.base.url(search_url))
             .query(&[
-                ("w",  format!("{} {}", song.title, song.artist).as_str()),
+                ("w", format!("{} {}", song.title, song.artist).as_str()),
                 ("format", "json"),
-                ("p","1"), // page
-                ("n", "1"),// 每页数量
+                ("p", "1"),  // page
+                ("n", "1"),  // 每页数量
                 ("cr", "1"), // 中文
-                ("t","0") // 搜索类型 0 歌曲
-                // ("g_tk", "5381"), //
+                ("t", "0"),  // 搜索类型 0 歌曲
+                             // ("g_tk", "5381"), //
             ])
             .header("Referer", "https://y.qq.com/n/ryqq/player")
-            .header("Host", "c.y.qq.com")
-            .header("Origin", "https://y.qq.com")
-            .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/102.0.5005.63 Safari/537.36");
+            .header("Origin", "https://y.qq.com");
 
         let data = self.base.fetch_with_retry::<Response>(request).await?;
         debug!("Get song: {:?}, info: {:?}", data, song);
@@ -87,14 +294,20 @@ impl LyricsFetcher for QQMusicFetcher {
                     .collect::<Vec<String>>()
                     .join(" ");
                 let album = s.albumname;
-                let params = vec![("songmid".to_string(), s.songmid)];
+                let duration = s.interval.map(|secs| secs as f64);
 
                 LyricsItem {
                     source,
                     title,
                     artist,
                     album,
-                    params,
+                    duration,
+                    id: SourceId::QQMusic {
+                        songmid: s.songmid,
+                        musicid: s.songid,
+                    },
+                    kinds: KINDS,
+                    score: 0.0,
                 }
             })
             .collect();
@@ -109,75 +322,95 @@ impl LyricsFetcher for QQMusicFetcher {
         }
     }
 
-    async fn download_lyric(&self, item: &LyricsItem) -> Result<String, LyricsError> {
-        let mut params = item.params.clone();
-        params.append(&mut vec![
-            ("format".to_string(), "json".to_string()),
-            ("g_tk".to_string(), "5381".to_string()),
-        ]);
+    async fn download_lyric(&self, item: &LyricsItem) -> Result<LyricsDoc, LyricsError> {
+        let SourceId::QQMusic { songmid, musicid } = &item.id else {
+            return Err(LyricsError::NoLyricsFound);
+        };
 
-        // 2. 获取歌词
-        let lyrics_url = "https://c.y.qq.com/lyric/fcgi-bin/fcg_query_lyric_new.fcg";
-        let request = self
-            .base
-            .client
-            .get(lyrics_url)
-            .query(&params)
-            .header("Referer", "https://y.qq.com/n/ryqq/player")
-            .header("Host", "c.y.qq.com")
-            .header("Origin", "https://y.qq.com");
+        // 2. 获取歌词, 优先逐字的 QRC，缺失时回退到 LRC
+        match self.download_qrc(*musicid).await {
+            Ok(doc) => return Ok(doc),
+            Err(e) => warn!("QQMusic qrc failed: {}", e),
+        }
+        self.download_lrc(songmid).await
+    }
 
-        let data: LyricsData = self.base.fetch_with_retry(request).await?;
+    fn source_name(&self) -> &'static str {
+        "QQMusic"
+    }
+}
 
-        // 处理Base64解码
-        let decoded = BASE64_STANDARD
-            .decode(data.lyric)
-            .map_err(|_| LyricsError::LyricsDecodeError)?;
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
 
-        let re = String::from_utf8(decoded).map_err(|_| LyricsError::LyricsDecodeError)?;
-        if re.is_empty() {
-            return Err(LyricsError::NoLyricsFound);
-        }
-        Ok(re)
+    use flate2::{Compression, write::ZlibEncoder};
+
+    use super::*;
+
+    const QRC: &str = "[ti:晴天]\n[ar:周杰伦]\n[0,2000]故(0,500)事(500,500)的(1000,1000)\n[2500,1500]Hello (2500,700)(world)(3200,800)";
+
+    /// 按 QQ 的格式打包: XML -> zlib -> 三重 DES -> hex
+    fn encrypt_qrc(xml: &str) -> String {
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(xml.as_bytes()).unwrap();
+        let mut bytes = encoder.finish().unwrap();
+        bytes.resize(bytes.len().next_multiple_of(8), 0);
+
+        des::tests::triple_des_encrypt(&mut bytes, des::QRC_KEY);
+        bytes.iter().map(|b| format!("{b:02X}")).collect()
     }
-    async fn fetch_lyric(&self, song: &SongInfo) -> Result<String, LyricsError> {
-        debug!("QQ search");
-
-        // let song_mid = data
-        //     .data
-        //     .song
-        //     .list
-        //     .into_iter()
-        //     .filter(|s| s.songname == song.title || s.songname.contains(&song.title))
-        //     .filter(|s| {
-        //         if song.artist.is_empty() {
-        //             true
-        //         } else {
-        //             s.singer.iter().find(|&a| a.name == song.artist).is_some()
-        //         }
-        //     })
-        //     .filter(|s| {
-        //         if song.album.is_empty() {
-        //             true
-        //         } else {
-        //             let a = s.albumname.to_lowercase();
-        //             let b = song.album.to_lowercase();
-        //             a == b || a.contains(&b)
-        //         }
-        //     })
-        //     .next()
-        //     .map(|s| s.songmid)
-        //     .ok_or(LyricsError::NoLyricsFound)?;
-
-        // debug!("song mid : {song_mid}");
-
-        let list = self.search_lyric(song).await?;
-        let item = get_first(list, song)?;
-        debug!("Get song: {:?} info: {:?}", item, song);
-        self.download_lyric(&item).await
+
+    #[test]
+    fn qrc_converts_to_enhanced_lrc() {
+        assert_eq!(
+            qrc_to_lrc(QRC),
+            "[ti:晴天]\n[ar:周杰伦]\n\
+             [00:00.00]<00:00.00>故<00:00.50>事<00:01.00>的<00:02.00>\n\
+             [00:02.50]<00:02.50>Hello <00:03.20>(world)<00:04.00>"
+        );
     }
 
-    fn source_name(&self) -> &'static str {
-        "QQMusic"
+    #[test]
+    fn decode_qrc_fixture() {
+        let xml = format!(
+            "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<QrcInfos>\n<LyricInfo LyricCount=\"1\">\n\
+             <Lyric_1 LyricType=\"1\" LyricContent=\"{}\"/>\n</LyricInfo>\n</QrcInfos>",
+            QRC.replace('"', "&quot;")
+        );
+        let encrypted = encrypt_qrc(&xml);
+
+        assert_eq!(decrypt_qrc(&encrypted).unwrap(), xml);
+        assert_eq!(decode_qrc(&encrypted).unwrap(), qrc_to_lrc(QRC));
+    }
+
+    /// `lyric_download.fcg` 中 `content` 的密文及其明文，离线按相同算法生成，待替换为实际抓包
+    const QRC_HEX: &str = include_str!("fixtures/qqmusic_qrc.hex");
+    const QRC_XML: &str = include_str!("fixtures/qqmusic_qrc.xml");
+
+    #[test]
+    fn decode_qrc_download() {
+        let encrypted = QRC_HEX.trim();
+        assert_eq!(decrypt_qrc(encrypted).unwrap(), QRC_XML);
+        assert_eq!(
+            decode_qrc(encrypted).unwrap(),
+            "[ti:晚风]\n[ar:测试歌手]\n[al:测试专辑]\n[by:]\n[offset:0]\n\
+             [00:01.62]<00:01.62>晚<00:02.02>风<00:02.37>吹<00:02.97>过<00:03.48>旧<00:03.93>街<00:04.63>\n\
+             [00:05.03]<00:05.03>Hello <00:05.55><00:05.73>world<00:07.31>\n\
+             [00:07.80]<00:07.80>\"Say <00:08.40>goodbye\" <00:09.10>(to you)<00:10.20>"
+        );
+
+        // 密文经过改动的 S 盒项，标准 DES 无法解出
+        let mut bytes = decode_hex(encrypted).unwrap();
+        des::tests::standard_triple_des_decrypt(&mut bytes, des::QRC_KEY);
+        let mut text = String::new();
+        let standard = ZlibDecoder::new(bytes.as_slice()).read_to_string(&mut text);
+        assert!(standard.is_err() || text != QRC_XML);
+    }
+
+    #[test]
+    fn decode_qrc_rejects_bad_input() {
+        assert!(decode_qrc("not hex").is_err());
+        assert!(decode_qrc("0011223344556677").is_err());
     }
 }
@@ -0,0 +1,278 @@
+//! QQ 音乐 QRC 使用的三重 DES
+//!
+//! QQ 客户端的实现与标准 DES 有出入：分组和密钥按两个小端 32 位整数读写，
+//! 且 S 盒 2、S 盒 4 各有一处数值不同，因此不能直接使用标准库实现。
+
+/// QRC 解密密钥
+pub(super) const QRC_KEY: &[u8; 24] = b"!@#)(*$%123ZXC!@!@#)(NHL";
+
+const IP: [u8; 64] = [
+    58, 50, 42, 34, 26, 18, 10, 2, 60, 52, 44, 36, 28, 20, 12, 4, //
+    62, 54, 46, 38, 30, 22, 14, 6, 64, 56, 48, 40, 32, 24, 16, 8, //
+    57, 49, 41, 33, 25, 17, 9, 1, 59, 51, 43, 35, 27, 19, 11, 3, //
+    61, 53, 45, 37, 29, 21, 13, 5, 63, 55, 47, 39, 31, 23, 15, 7,
+];
+
+const FP: [u8; 64] = [
+    40, 8, 48, 16, 56, 24, 64, 32, 39, 7, 47, 15, 55, 23, 63, 31, //
+    38, 6, 46, 14, 54, 22, 62, 30, 37, 5, 45, 13, 53, 21, 61, 29, //
+    36, 4, 44, 12, 52, 20, 60, 28, 35, 3, 43, 11, 51, 19, 59, 27, //
+    34, 2, 42, 10, 50, 18, 58, 26, 33, 1, 41, 9, 49, 17, 57, 25,
+];
+
+const E: [u8; 48] = [
+    32, 1, 2, 3, 4, 5, 4, 5, 6, 7, 8, 9, //
+    8, 9, 10, 11, 12, 13, 12, 13, 14, 15, 16, 17, //
+    16, 17, 18, 19, 20, 21, 20, 21, 22, 23, 24, 25, //
+    24, 25, 26, 27, 28, 29, 28, 29, 30, 31, 32, 1,
+];
+
+const P: [u8; 32] = [
+    16, 7, 20, 21, 29, 12, 28, 17, 1, 15, 23, 26, 5, 18, 31, 10, //
+    2, 8, 24, 14, 32, 27, 3, 9, 19, 13, 30, 6, 22, 11, 4, 25,
+];
+
+const PC1: [u8; 56] = [
+    57, 49, 41, 33, 25, 17, 9, 1, 58, 50, 42, 34, 26, 18, //
+    10, 2, 59, 51, 43, 35, 27, 19, 11, 3, 60, 52, 44, 36, //
+    63, 55, 47, 39, 31, 23, 15, 7, 62, 54, 46, 38, 30, 22, //
+    14, 6, 61, 53, 45, 37, 29, 21, 13, 5, 28, 20, 12, 4,
+];
+
+const PC2: [u8; 48] = [
+    14, 17, 11, 24, 1, 5, 3, 28, 15, 6, 21, 10, //
+    23, 19, 12, 4, 26, 8, 16, 7, 27, 20, 13, 2, //
+    41, 52, 31, 37, 47, 55, 30, 40, 51, 45, 33, 48, //
+    44, 49, 39, 56, 34, 53, 46, 42, 50, 36, 29, 32,
+];
+
+const SHIFTS: [u32; 16] = [1, 1, 2, 2, 2, 2, 2, 2, 1, 2, 2, 2, 2, 2, 2, 1];
+
+const SBOX: [[u8; 64]; 8] = [
+    [
+        14, 4, 13, 1, 2, 15, 11, 8, 3, 10, 6, 12, 5, 9, 0, 7, //
+        0, 15, 7, 4, 14, 2, 13, 1, 10, 6, 12, 11, 9, 5, 3, 8, //
+        4, 1, 14, 8, 13, 6, 2, 11, 15, 12, 9, 7, 3, 10, 5, 0, //
+        15, 12, 8, 2, 4, 9, 1, 7, 5, 11, 3, 14, 10, 0, 6, 13,
+    ],
+    [
+        15, 1, 8, 14, 6, 11, 3, 4, 9, 7, 2, 13, 12, 0, 5, 10, //
+        3, 13, 4, 7, 15, 2, 8, 15, 12, 0, 1, 10, 6, 9, 11, 5, // 标准值为 14
+        0, 14, 7, 11, 10, 4, 13, 1, 5, 8, 12, 6, 9, 3, 2, 15, //
+        13, 8, 10, 1, 3, 15, 4, 2, 11, 6, 7, 12, 0, 5, 14, 9,
+    ],
+    [
+        10, 0, 9, 14, 6, 3, 15, 5, 1, 13, 12, 7, 11, 4, 2, 8, //
+        13, 7, 0, 9, 3, 4, 6, 10, 2, 8, 5, 14, 12, 11, 15, 1, //
+        13, 6, 4, 9, 8, 15, 3, 0, 11, 1, 2, 12, 5, 10, 14, 7, //
+        1, 10, 13, 0, 6, 9, 8, 7, 4, 15, 14, 3, 11, 5, 2, 12,
+    ],
+    [
+        7, 13, 14, 3, 0, 6, 9, 10, 1, 2, 8, 5, 11, 12, 4, 15, //
+        13, 8, 11, 5, 6, 15, 0, 3, 4, 7, 2, 12, 1, 10, 14, 9, //
+        10, 6, 9, 0, 12, 11, 7, 13, 15, 1, 3, 14, 5, 2, 8, 4, //
+        3, 15, 0, 6, 10, 10, 13, 8, 9, 4, 5, 11, 12, 7, 2, 14, // 标准值为 1
+    ],
+    [
+        2, 12, 4, 1, 7, 10, 11, 6, 8, 5, 3, 15, 13, 0, 14, 9, //
+        14, 11, 2, 12, 4, 7, 13, 1, 5, 0, 15, 10, 3, 9, 8, 6, //
+        4, 2, 1, 11, 10, 13, 7, 8, 15, 9, 12, 5, 6, 3, 0, 14, //
+        11, 8, 12, 7, 1, 14, 2, 13, 6, 15, 0, 9, 10, 4, 5, 3,
+    ],
+    [
+        12, 1, 10, 15, 9, 2, 6, 8, 0, 13, 3, 4, 14, 7, 5, 11, //
+        10, 15, 4, 2, 7, 12, 9, 5, 6, 1, 13, 14, 0, 11, 3, 8, //
+        9, 14, 15, 5, 2, 8, 12, 3, 7, 0, 4, 10, 1, 13, 11, 6, //
+        4, 3, 2, 12, 9, 5, 15, 10, 11, 14, 1, 7, 6, 0, 8, 13,
+    ],
+    [
+        4, 11, 2, 14, 15, 0, 8, 13, 3, 12, 9, 7, 5, 10, 6, 1, //
+        13, 0, 11, 7, 4, 9, 1, 10, 14, 3, 5, 12, 2, 15, 8, 6, //
+        1, 4, 11, 13, 12, 3, 7, 14, 10, 15, 6, 8, 0, 5, 9, 2, //
+        6, 11, 13, 8, 1, 4, 10, 7, 9, 5, 0, 15, 14, 2, 3, 12,
+    ],
+    [
+        13, 2, 8, 4, 6, 15, 11, 1, 10, 9, 3, 14, 5, 0, 12, 7, //
+        1, 15, 13, 8, 10, 3, 7, 4, 12, 5, 6, 11, 0, 14, 9, 2, //
+        7, 11, 4, 1, 9, 12, 14, 2, 0, 6, 10, 13, 15, 3, 5, 8, //
+        2, 1, 14, 7, 4, 10, 8, 13, 15, 12, 9, 0, 3, 5, 6, 11,
+    ],
+];
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Encrypt,
+    Decrypt,
+}
+
+/// 按表置换，`width` 为输入位数，表中位号从最高位 1 开始
+fn permute(input: u64, width: u32, table: &[u8]) -> u64 {
+    table.iter().fold(0, |out, &bit| {
+        (out << 1) | ((input >> (width - bit as u32)) & 1)
+    })
+}
+
+/// QQ 的实现将 8 字节按两个小端 32 位整数读写
+fn load(block: &[u8]) -> u64 {
+    let hi = u32::from_le_bytes([block[0], block[1], block[2], block[3]]);
+    let lo = u32::from_le_bytes([block[4], block[5], block[6], block[7]]);
+    ((hi as u64) << 32) | lo as u64
+}
+
+fn store(value: u64, block: &mut [u8]) {
+    block[..4].copy_from_slice(&((value >> 32) as u32).to_le_bytes());
+    block[4..8].copy_from_slice(&(value as u32).to_le_bytes());
+}
+
+fn key_schedule(key: &[u8], mode: Mode) -> [u64; 16] {
+    let pc1 = permute(load(key), 64, &PC1);
+    let (mut c, mut d) = ((pc1 >> 28) as u32, (pc1 & 0x0fff_ffff) as u32);
+
+    let mut schedule = [0; 16];
+    for (i, shift) in SHIFTS.iter().enumerate() {
+        c = ((c << shift) | (c >> (28 - shift))) & 0x0fff_ffff;
+        d = ((d << shift) | (d >> (28 - shift))) & 0x0fff_ffff;
+        let index = match mode {
+            Mode::Encrypt => i,
+            Mode::Decrypt => 15 - i,
+        };
+        schedule[index] = permute(((c as u64) << 28) | d as u64, 56, &PC2);
+    }
+    schedule
+}
+
+fn feistel(half: u32, subkey: u64, sbox: &[[u8; 64]; 8]) -> u32 {
+    let expanded = permute(half as u64, 32, &E) ^ subkey;
+    let substituted = sbox.iter().enumerate().fold(0u64, |out, (i, sbox)| {
+        let chunk = (expanded >> (42 - 6 * i)) & 0x3f;
+        let row = ((chunk >> 4) & 0b10) | (chunk & 1);
+        let col = (chunk >> 1) & 0xf;
+        (out << 4) | sbox[(row * 16 + col) as usize] as u64
+    });
+    permute(substituted, 32, &P) as u32
+}
+
+fn crypt_block(block: &mut [u8], schedule: &[u64; 16], sbox: &[[u8; 64]; 8]) {
+    let state = permute(load(block), 64, &IP);
+    let (mut left, mut right) = ((state >> 32) as u32, state as u32);
+
+    for subkey in schedule {
+        let next = left ^ feistel(right, *subkey, sbox);
+        left = right;
+        right = next;
+    }
+
+    let output = permute(((right as u64) << 32) | left as u64, 64, &FP);
+    store(output, block);
+}
+
+/// 三重 DES (EDE) 解密，ECB 模式
+pub(super) fn triple_des_decrypt(data: &mut [u8], key: &[u8; 24]) {
+    let schedules = [
+        key_schedule(&key[16..24], Mode::Decrypt),
+        key_schedule(&key[8..16], Mode::Encrypt),
+        key_schedule(&key[..8], Mode::Decrypt),
+    ];
+
+    for block in data.chunks_exact_mut(8) {
+        for schedule in &schedules {
+            crypt_block(block, schedule, &SBOX);
+        }
+    }
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    /// 三重 DES (EDE) 加密，用于生成测试数据
+    pub(in super::super) fn triple_des_encrypt(data: &mut [u8], key: &[u8; 24]) {
+        let schedules = [
+            key_schedule(&key[..8], Mode::Encrypt),
+            key_schedule(&key[8..16], Mode::Decrypt),
+            key_schedule(&key[16..24], Mode::Encrypt),
+        ];
+
+        for block in data.chunks_exact_mut(8) {
+            for schedule in &schedules {
+                crypt_block(block, schedule, &SBOX);
+            }
+        }
+    }
+
+    /// 使用标准 S 盒解密，用于确认测试数据依赖改动的两处
+    pub(in super::super) fn standard_triple_des_decrypt(data: &mut [u8], key: &[u8; 24]) {
+        let sbox = standard_sbox();
+        let schedules = [
+            key_schedule(&key[16..24], Mode::Decrypt),
+            key_schedule(&key[8..16], Mode::Encrypt),
+            key_schedule(&key[..8], Mode::Decrypt),
+        ];
+
+        for block in data.chunks_exact_mut(8) {
+            for schedule in &schedules {
+                crypt_block(block, schedule, &sbox);
+            }
+        }
+    }
+
+    /// 还原两处改动后的标准 S 盒
+    fn standard_sbox() -> [[u8; 64]; 8] {
+        let mut sbox = SBOX;
+        sbox[1][23] = 14;
+        sbox[3][53] = 1;
+        sbox
+    }
+
+    /// 按 QQ 的字节序写入 64 位整数
+    fn block(value: u64) -> [u8; 8] {
+        let mut block = [0; 8];
+        store(value, &mut block);
+        block
+    }
+
+    #[test]
+    fn only_documented_sbox_entries_differ() {
+        assert_eq!(SBOX[1][23], 15);
+        assert_eq!(SBOX[3][53], 10);
+        let differ = SBOX
+            .iter()
+            .flatten()
+            .zip(standard_sbox().iter().flatten())
+            .filter(|(a, b)| a != b)
+            .count();
+        assert_eq!(differ, 2);
+    }
+
+    /// 换回标准 S 盒后应符合 DES 的已知答案
+    #[test]
+    fn standard_des_known_answers() {
+        let sbox = standard_sbox();
+        for (key, plain, cipher) in [
+            (0x133457799bbcdff1, 0x0123456789abcdef, 0x85e813540f0ab405),
+            (0x0000000000000000, 0x0000000000000000, 0x8ca64de9c1b123a7),
+            (0xffffffffffffffff, 0xffffffffffffffff, 0x7359b2163e4edc58),
+            (0x3000000000000000, 0x1000000000000001, 0x958e6e627a05557b),
+            (0x1111111111111111, 0x1111111111111111, 0xf40379ab9e0ec533),
+            (0x0123456789abcdef, 0x1111111111111111, 0x17668dfc7292532d),
+            (0xfedcba9876543210, 0x0123456789abcdef, 0xed39d950fa74bcc4),
+        ] {
+            let key = block(key);
+            let mut data = block(plain);
+            crypt_block(&mut data, &key_schedule(&key, Mode::Encrypt), &sbox);
+            assert_eq!(load(&data), cipher, "key {:016x?}", key);
+            crypt_block(&mut data, &key_schedule(&key, Mode::Decrypt), &sbox);
+            assert_eq!(load(&data), plain);
+        }
+    }
+
+    #[test]
+    fn triple_des_round_trip() {
+        let plain = *b"QRC test block 16";
+        let mut data = plain;
+        triple_des_encrypt(&mut data[..16], QRC_KEY);
+        assert_ne!(data, plain);
+        triple_des_decrypt(&mut data[..16], QRC_KEY);
+        assert_eq!(data, plain);
+    }
+}
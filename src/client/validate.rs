@@ -0,0 +1,104 @@
+//! 下载后的歌词校验
+//!
+//! 拒绝没有时间轴、时长明显不符、时间标签错乱以及只有制作信息的歌词。
+
+use tracing::warn;
+
+use crate::{
+    config::get_config,
+    error::LyricsError,
+    song::{LyricParser, LyricsDoc, LyricsFormat, SongInfo},
+};
+
+/// 制作信息行冒号前的关键词，如 `作词 : xxx`
+const CREDIT_KEYWORDS: [&str; 16] = [
+    "词", "曲", "编", "制作", "监制", "混音", "录音", "和声", "母带", "出品", "lyric", "compos",
+    "arrange", "produce", "mix", "written",
+];
+
+/// 冒号前的关键词最长字符数
+const CREDIT_KEY_CHARS: usize = 12;
+
+pub(super) async fn validate(song: &SongInfo, doc: &LyricsDoc) -> Result<(), LyricsError> {
+    match rejection(song, doc).await {
+        Some(reason) => {
+            warn!(
+                "Reject lyrics for {} - {}: {}",
+                song.artist, song.title, reason
+            );
+            Err(LyricsError::LyricsValidationFailed)
+        }
+        None => Ok(()),
+    }
+}
+
+/// 不通过的原因
+async fn rejection(song: &SongInfo, doc: &LyricsDoc) -> Option<&'static str> {
+    let (enabled, allow_unsynced, max_overrun, min_coverage, max_disorder) = {
+        let config = &get_config().read().unwrap().validation;
+        (
+            config.enabled,
+            config.allow_unsynced,
+            config.max_overrun,
+            config.min_coverage,
+            config.max_disorder,
+        )
+    };
+    if !enabled {
+        return None;
+    }
+
+    let Ok(lyrics) = LyricParser::parse(doc.lyric.clone(), song.duration).await else {
+        return Some("empty or undecodable");
+    };
+
+    let lyric_lines: Vec<_> = lyrics
+        .lines
+        .iter()
+        .filter(|l| !l.text.is_empty() && !is_credit(&l.text))
+        .collect();
+    if lyric_lines.is_empty() {
+        return Some("metadata only");
+    }
+
+    if lyrics.unsynced {
+        return (!allow_unsynced).then_some("no timestamps");
+    }
+
+    if song.duration > 0.0 {
+        let last = lyric_lines.last().map_or(0.0, |l| l.timestamp_start);
+        if last > song.duration + max_overrun {
+            return Some("runs past the song");
+        }
+        if last < song.duration * min_coverage {
+            return Some("ends too early");
+        }
+    }
+
+    // 逐行比较原始顺序中的时间标签；
+    // 双语歌词的译文与原文共用时间标签，相同时间只在文本也相同时算作重复
+    if LyricsFormat::detect(&doc.lyric) == LyricsFormat::Lrc {
+        let starts = LyricParser::line_starts(&doc.lyric).await;
+        let disordered = starts
+            .windows(2)
+            .filter(|w| w[1].0 < w[0].0 || w[1] == w[0])
+            .count();
+        if starts.len() > 1 && disordered as f64 / (starts.len() - 1) as f64 > max_disorder {
+            return Some("non-monotonic or duplicate timestamps");
+        }
+    }
+
+    None
+}
+
+/// `作词 : xxx`、`Composer: xxx` 等制作信息
+fn is_credit(text: &str) -> bool {
+    let Some((key, _)) = text.split_once([':', '：']) else {
+        return false;
+    };
+    if key.chars().count() > CREDIT_KEY_CHARS {
+        return false;
+    }
+    let key = key.to_lowercase();
+    CREDIT_KEYWORDS.iter().any(|k| key.contains(k))
+}
@@ -1,9 +1,12 @@
 use anyhow::{Context, Result};
-use serde::Deserialize;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Deserializer};
 use std::{
-    fs,
+    collections::BTreeMap,
+    fmt, fs,
     path::PathBuf,
     sync::{OnceLock, RwLock},
+    time::Duration,
 };
 use tracing::debug;
 
@@ -22,6 +25,8 @@ pub struct Config {
     pub player_filter: PlayerFilter,
     pub ui: Ui,
     pub sources: Sources,
+    pub sync: SyncOptions,
+    pub validation: Validation,
 }
 
 #[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
@@ -31,6 +36,10 @@ pub enum PlayerProtocol {
     Auto,
     Mpris,
     Mpd,
+    Cmus,
+    /// 由 play 子命令启用，不能在配置中选择
+    #[serde(skip)]
+    Clock,
 }
 
 #[derive(Debug, Deserialize)]
@@ -41,10 +50,203 @@ pub struct PlayerFilter {
     pub only: Vec<String>,
     #[serde(default = "default_player_except")]
     pub except: Vec<String>,
-    #[serde(default = "default_mpd_host")]
-    pub mpd_host: String,
-    #[serde(default = "default_mpd_port")]
-    pub mpd_port: u16,
+    /// `host`、`host:port`、`password@host` 或 Unix socket 路径，未设置时读取 MPD_HOST
+    #[serde(default)]
+    pub mpd_host: Option<String>,
+    /// 未设置时读取 MPD_PORT
+    #[serde(default)]
+    pub mpd_port: Option<u16>,
+    /// cmus socket 路径，未设置时与 cmus-remote 相同
+    #[serde(default)]
+    pub cmus_socket: Option<String>,
+    /// MPRIS 过滤规则，按顺序匹配，优先于 only / except
+    #[serde(default)]
+    pub rules: Vec<PlayerRule>,
+}
+
+impl PlayerFilter {
+    /// 是否使用该 MPRIS 播放器，`url_host` 仅在有规则时读取
+    pub fn allows(
+        &self,
+        bus_name: &str,
+        identity: &str,
+        url_host: impl FnOnce() -> Option<String>,
+    ) -> bool {
+        // 规则按顺序匹配，第一条命中的规则决定结果
+        if !self.rules.is_empty() {
+            let url = url_host();
+            if let Some(rule) = self
+                .rules
+                .iter()
+                .find(|r| r.matches(bus_name, identity, url.as_deref()))
+            {
+                return rule.action == RuleAction::Allow;
+            }
+        }
+
+        let identity = identity.to_lowercase();
+        if !self.except.is_empty() && self.except.iter().any(|k| identity.contains(k)) {
+            return false;
+        }
+
+        if !self.only.is_empty() {
+            return self.only.iter().any(|k| identity.contains(k));
+        }
+
+        true
+    }
+
+    /// MPD 连接目标，配置优先，其次为 MPD_HOST / MPD_PORT 环境变量
+    pub fn mpd_target(&self) -> MpdTarget {
+        self.mpd_target_with(|key| std::env::var(key).ok())
+    }
+
+    /// 从 `env` 读取环境变量
+    fn mpd_target_with(&self, env: impl Fn(&str) -> Option<String>) -> MpdTarget {
+        let host = self
+            .mpd_host
+            .clone()
+            .or_else(|| env("MPD_HOST"))
+            .filter(|h| !h.is_empty())
+            .unwrap_or_else(default_mpd_host);
+        let port = self
+            .mpd_port
+            .or_else(|| env("MPD_PORT")?.parse().ok())
+            .unwrap_or_else(default_mpd_port);
+        MpdTarget::parse(&host, port)
+    }
+
+    /// cmus socket 路径，依次为配置、CMUS_SOCKET、$XDG_RUNTIME_DIR/cmus-socket、~/.config/cmus/socket
+    pub fn cmus_socket_path(&self) -> PathBuf {
+        if let Some(path) = self
+            .cmus_socket
+            .clone()
+            .or_else(|| std::env::var("CMUS_SOCKET").ok())
+            .filter(|p| !p.is_empty())
+        {
+            return expand_home(&path);
+        }
+        if let Some(runtime) = std::env::var_os("XDG_RUNTIME_DIR") {
+            return PathBuf::from(runtime).join("cmus-socket");
+        }
+        dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".config")
+            .join("cmus")
+            .join("socket")
+    }
+}
+
+/// 展开 `~/` 开头的路径
+fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/") {
+        Some(rest) => dirs::home_dir().unwrap_or_default().join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// MPD 连接地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MpdAddress {
+    Tcp(String, u16),
+    Unix(PathBuf),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MpdTarget {
+    pub address: MpdAddress,
+    pub password: Option<String>,
+}
+
+impl MpdTarget {
+    /// 与 mpc 相同：`password@host`，以 `/` 或 `~` 开头的为 Unix socket；
+    /// `host:port` 中的端口优先于 `port`，IPv6 地址写作 `[::1]:6600`
+    fn parse(host: &str, port: u16) -> Self {
+        let (password, host) = match host.split_once('@') {
+            Some((password, host)) => ((!password.is_empty()).then(|| password.to_string()), host),
+            None => (None, host),
+        };
+
+        let address = if host.starts_with('/') || host.starts_with("~/") {
+            MpdAddress::Unix(expand_home(host))
+        } else {
+            let (host, port) = split_port(host).unwrap_or((host, port));
+            MpdAddress::Tcp(host.to_string(), port)
+        };
+
+        Self { address, password }
+    }
+}
+
+/// 拆分 `host:port`，没有方括号的 IPv6 地址不含端口
+fn split_port(host: &str) -> Option<(&str, u16)> {
+    let (host, port) = host.rsplit_once(':')?;
+    if host.contains(':') && !host.ends_with(']') {
+        return None;
+    }
+    let host = host.trim_start_matches('[').trim_end_matches(']');
+    Some((host, port.parse().ok()?))
+}
+
+/// 不包含密码，用于日志和显示
+impl fmt::Display for MpdTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.address {
+            MpdAddress::Tcp(host, port) => write!(f, "{}:{}", host, port),
+            MpdAddress::Unix(path) => write!(f, "{}", path.display()),
+        }
+    }
+}
+
+/// 规则命中后的处理
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RuleAction {
+    Allow,
+    Deny,
+}
+
+/// MPRIS 过滤规则，设置的条件须全部满足才算命中
+#[derive(Debug, Deserialize)]
+pub struct PlayerRule {
+    pub action: RuleAction,
+    /// 总线名称，如 `org.mpris.MediaPlayer2.firefox.instance_1_42`
+    #[serde(default)]
+    pub bus_name: Option<Pattern>,
+    /// 播放器 identity
+    #[serde(default)]
+    pub identity: Option<Pattern>,
+    /// 当前曲目 `xesam:url` 的主机名
+    #[serde(default)]
+    pub url: Option<Pattern>,
+}
+
+impl PlayerRule {
+    pub fn matches(&self, bus_name: &str, identity: &str, url_host: Option<&str>) -> bool {
+        let matches = |pattern: &Option<Pattern>, value: Option<&str>| match pattern {
+            Some(pattern) => value.is_some_and(|v| pattern.0.is_match(v)),
+            None => true,
+        };
+
+        matches(&self.bus_name, Some(bus_name))
+            && matches(&self.identity, Some(identity))
+            && matches(&self.url, url_host)
+    }
+}
+
+/// 忽略大小写的正则表达式
+#[derive(Debug)]
+pub struct Pattern(pub Regex);
+
+impl<'de> Deserialize<'de> for Pattern {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        RegexBuilder::new(&s)
+            .case_insensitive(true)
+            .build()
+            .map(Pattern)
+            .map_err(serde::de::Error::custom)
+    }
 }
 
 fn default_player_except() -> Vec<String> {
@@ -71,8 +273,10 @@ impl Default for PlayerFilter {
             protocol: PlayerProtocol::Mpris,
             only: vec![],
             except: default_player_except(),
-            mpd_host: default_mpd_host(),
-            mpd_port: default_mpd_port(),
+            mpd_host: None,
+            mpd_port: None,
+            cmus_socket: None,
+            rules: vec![],
         }
     }
 }
@@ -87,6 +291,13 @@ pub struct Ui {
     pub progress_bar: bool,
     #[serde(default)]
     pub text_center: bool,
+    #[serde(default)]
+    pub translation: TranslationMode,
+    #[serde(default)]
+    pub romanization: RomanizationMode,
+    /// 无时间轴歌词按播放进度自动滚动
+    #[serde(default)]
+    pub unsynced_auto_scroll: bool,
 }
 
 impl Default for Ui {
@@ -96,26 +307,274 @@ impl Default for Ui {
             time: false,
             progress_bar: true,
             text_center: false,
+            translation: TranslationMode::Below,
+            romanization: RomanizationMode::Both,
+            unsynced_auto_scroll: false,
+        }
+    }
+}
+
+/// 翻译显示方式
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TranslationMode {
+    /// 在原文下方
+    #[default]
+    Below,
+    /// 与原文左右并列
+    SideBySide,
+    Hidden,
+}
+
+impl TranslationMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Below => Self::SideBySide,
+            Self::SideBySide => Self::Hidden,
+            Self::Hidden => Self::Below,
         }
     }
 }
 
 #[derive(Debug, Deserialize)]
 pub struct Sources {
-    #[serde(default = "default_true")]
-    pub netease: bool,
-    #[serde(default = "default_true")]
-    pub qq: bool,
-    #[serde(default = "default_true")]
-    pub kugou: bool,
+    /// 单个来源的默认超时（毫秒）
+    #[serde(default = "default_source_timeout")]
+    pub timeout: u64,
+    /// 自动获取歌词的总等待时间（毫秒），到时使用已返回的结果
+    #[serde(default = "default_fetch_deadline")]
+    pub deadline: u64,
+    /// 自动匹配的最低分数（0 ~ 1），低于该分数的候选不会自动下载
+    #[serde(default = "default_min_score")]
+    pub min_score: f64,
+    /// 各来源的设置 `[sources.<name>]`，未列出的来源使用默认设置
+    #[serde(flatten, deserialize_with = "deserialize_fetchers")]
+    pub fetchers: BTreeMap<String, SourceSetting>,
 }
 
 impl Default for Sources {
     fn default() -> Self {
         Sources {
-            netease: true,
-            qq: true,
-            kugou: true,
+            timeout: default_source_timeout(),
+            deadline: default_fetch_deadline(),
+            min_score: default_min_score(),
+            fetchers: BTreeMap::new(),
+        }
+    }
+}
+
+/// 来源设置，兼容旧的 `netease = true` 写法
+#[derive(Debug, Clone)]
+pub enum SourceSetting {
+    Enabled(bool),
+    Options(SourceOptions),
+}
+
+/// 只接受已注册的来源名称，拼写错误的键或来源给出明确的错误
+fn deserialize_fetchers<'de, D>(
+    deserializer: D,
+) -> Result<BTreeMap<String, SourceSetting>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde::de::Error;
+
+    let names: Vec<_> = crate::client::source_names().collect();
+    BTreeMap::<String, toml::Value>::deserialize(deserializer)?
+        .into_iter()
+        .map(|(name, value)| {
+            if !names.contains(&name.as_str()) {
+                return Err(D::Error::custom(format!(
+                    "unknown key `{name}` in [sources], expected `timeout`, `deadline`, `min_score` or a source: {}",
+                    names.join(", ")
+                )));
+            }
+            let setting = match value {
+                toml::Value::Boolean(enabled) => SourceSetting::Enabled(enabled),
+                value => SourceSetting::Options(
+                    SourceOptions::deserialize(value)
+                        .map_err(|e| D::Error::custom(format!("[sources.{name}]: {e}")))?,
+                ),
+            };
+            Ok((name, setting))
+        })
+        .collect()
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct SourceOptions {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 数值大的来源先请求，相同时按内置顺序
+    #[serde(default)]
+    pub priority: i32,
+    /// 超时（毫秒），未设置时使用 `sources.timeout`
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// 请求失败时的重试次数
+    #[serde(default = "default_source_retries")]
+    pub retries: u8,
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    /// 替换请求地址的协议与主机，用于代理或镜像
+    #[serde(default)]
+    pub base_url: Option<String>,
+    /// 排序时的先验加分，分数相近时优先该来源，不计入 `min_score`
+    #[serde(default)]
+    pub prior: f64,
+}
+
+impl Default for SourceOptions {
+    fn default() -> Self {
+        SourceOptions {
+            enabled: true,
+            priority: 0,
+            timeout: None,
+            retries: default_source_retries(),
+            user_agent: None,
+            base_url: None,
+            prior: 0.0,
+        }
+    }
+}
+
+impl Sources {
+    /// 来源的设置，`name` 为配置名称
+    pub fn source(&self, name: &str) -> SourceOptions {
+        match self.fetchers.get(name) {
+            Some(SourceSetting::Enabled(enabled)) => SourceOptions {
+                enabled: *enabled,
+                ..Default::default()
+            },
+            Some(SourceSetting::Options(options)) => options.clone(),
+            None => SourceOptions::default(),
+        }
+    }
+
+    /// 来源对应的超时
+    pub fn timeout_for(&self, name: &str) -> Duration {
+        Duration::from_millis(self.source(name).timeout.unwrap_or(self.timeout))
+    }
+
+    /// 来源的先验加分，未设置时为 0
+    pub fn prior_for(&self, name: &str) -> f64 {
+        self.source(name).prior
+    }
+
+    pub fn deadline(&self) -> Duration {
+        Duration::from_millis(self.deadline)
+    }
+}
+
+fn default_source_retries() -> u8 {
+    3
+}
+
+fn default_min_score() -> f64 {
+    0.6
+}
+
+fn default_source_timeout() -> u64 {
+    5000
+}
+
+fn default_fetch_deadline() -> u64 {
+    8000
+}
+
+/// 自动下载歌词后的校验，不通过时尝试下一个候选
+#[derive(Debug, Deserialize)]
+pub struct Validation {
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    /// 允许没有时间轴的歌词
+    #[serde(default)]
+    pub allow_unsynced: bool,
+    /// 最后一行允许超出歌曲时长的秒数
+    #[serde(default = "default_max_overrun")]
+    pub max_overrun: f64,
+    /// 最后一行至少到达歌曲时长的比例
+    #[serde(default = "default_min_coverage")]
+    pub min_coverage: f64,
+    /// 倒序或重复时间标签的行占比上限
+    #[serde(default = "default_max_disorder")]
+    pub max_disorder: f64,
+}
+
+impl Default for Validation {
+    fn default() -> Self {
+        Validation {
+            enabled: true,
+            allow_unsynced: false,
+            max_overrun: default_max_overrun(),
+            min_coverage: default_min_coverage(),
+            max_disorder: default_max_disorder(),
+        }
+    }
+}
+
+fn default_max_overrun() -> f64 {
+    15.0
+}
+
+fn default_min_coverage() -> f64 {
+    0.5
+}
+
+fn default_max_disorder() -> f64 {
+    0.2
+}
+
+/// 输出设备延迟补偿
+#[derive(Debug, Deserialize, Default)]
+pub struct SyncOptions {
+    /// 全局延迟（毫秒），正数使歌词延后
+    #[serde(default)]
+    pub latency: i64,
+    /// 按播放器覆盖，键为 MPRIS identity 关键词、MPD 地址或 cmus
+    #[serde(default)]
+    pub players: BTreeMap<String, i64>,
+}
+
+impl SyncOptions {
+    /// 播放器对应的延迟（秒），优先完全匹配，其次包含关键词
+    pub fn latency_for(&self, player: &str) -> f64 {
+        let player = player.to_lowercase();
+        let latency = self
+            .players
+            .iter()
+            .find(|(k, _)| k.to_lowercase() == player)
+            .or_else(|| {
+                self.players
+                    .iter()
+                    .find(|(k, _)| player.contains(&k.to_lowercase()))
+            })
+            .map(|(_, v)| *v)
+            .unwrap_or(self.latency);
+        latency as f64 / 1000.0
+    }
+}
+
+/// 罗马音显示方式
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum RomanizationMode {
+    /// 仅原文
+    Original,
+    /// 罗马音在原文上方
+    #[default]
+    Both,
+    /// 仅罗马音
+    Romanization,
+}
+
+impl RomanizationMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Original => Self::Both,
+            Self::Both => Self::Romanization,
+            Self::Romanization => Self::Original,
         }
     }
 }
@@ -164,6 +623,17 @@ pub fn config_path() -> PathBuf {
     config_dir
 }
 
+/// 保存的固定播放器
+pub fn pinned_player_path() -> PathBuf {
+    let path = dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".config")
+        .join(CONFIG_PATH)
+        .join("pinned-player");
+    ensure_parent_dir(&path);
+    path
+}
+
 pub fn log_path() -> PathBuf {
     let log_file = dirs::home_dir()
         .unwrap_or_else(|| PathBuf::from("."))
@@ -183,3 +653,192 @@ pub fn lyrics_path() -> PathBuf {
     ensure_parent_dir(&cache_dir);
     cache_dir
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(content: &str) -> Result<Config, String> {
+        toml::from_str(content).map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn sources_accept_registered_names() {
+        let config = parse(
+            "[sources]\ntimeout = 1000\nkugou = false\n\n[sources.netease]\ntimeout = 3000\nprior = 0.05\n",
+        )
+        .unwrap();
+        let sources = &config.sources;
+        assert!(!sources.source("kugou").enabled);
+        assert_eq!(sources.timeout_for("netease"), Duration::from_millis(3000));
+        assert_eq!(sources.timeout_for("qq"), Duration::from_millis(1000));
+        assert_eq!(sources.prior_for("netease"), 0.05);
+        // 只按名称完全匹配
+        assert_eq!(sources.prior_for("net"), 0.0);
+        assert_eq!(sources.prior_for("netease-mirror"), 0.0);
+    }
+
+    #[test]
+    fn sources_reject_unknown_keys() {
+        let error = parse("[sources]\ntimout = 3000\n").unwrap_err();
+        assert!(error.contains("unknown key `timout`"), "{error}");
+
+        let error = parse("[sources.kugo]\nenabled = false\n").unwrap_err();
+        assert!(error.contains("unknown key `kugo`"), "{error}");
+
+        let error = parse("[sources.netease]\ntimout = 3000\n").unwrap_err();
+        assert!(error.contains("[sources.netease]"), "{error}");
+        assert!(error.contains("timout"), "{error}");
+    }
+
+    fn tcp(host: &str, port: u16) -> MpdAddress {
+        MpdAddress::Tcp(host.to_string(), port)
+    }
+
+    #[test]
+    fn mpd_target_parses_hosts() {
+        let home = dirs::home_dir().unwrap_or_default();
+        let cases = [
+            ("localhost", tcp("localhost", 6600), None),
+            ("secret@music.lan", tcp("music.lan", 6600), Some("secret")),
+            ("music.lan:6601", tcp("music.lan", 6601), None),
+            (
+                "secret@music.lan:6601",
+                tcp("music.lan", 6601),
+                Some("secret"),
+            ),
+            ("[::1]:6601", tcp("::1", 6601), None),
+            ("::1", tcp("::1", 6600), None),
+            (
+                "secret@/run/mpd/socket",
+                MpdAddress::Unix("/run/mpd/socket".into()),
+                Some("secret"),
+            ),
+            (
+                "~/.local/run/mpd/socket",
+                MpdAddress::Unix(home.join(".local/run/mpd/socket")),
+                None,
+            ),
+            // 空密码按没有密码处理
+            ("@localhost", tcp("localhost", 6600), None),
+        ];
+        for (host, address, password) in cases {
+            let target = MpdTarget::parse(host, 6600);
+            assert_eq!(target.address, address, "{host}");
+            assert_eq!(target.password.as_deref(), password, "{host}");
+        }
+    }
+
+    #[test]
+    fn mpd_target_prefers_config_over_env() {
+        let env = |key: &str| match key {
+            "MPD_HOST" => Some("env.lan".to_string()),
+            "MPD_PORT" => Some("6700".to_string()),
+            _ => None,
+        };
+        let no_env = |_: &str| None;
+
+        let mut filter = PlayerFilter::default();
+        assert_eq!(
+            filter.mpd_target_with(no_env).address,
+            tcp("127.0.0.1", 6600)
+        );
+        assert_eq!(filter.mpd_target_with(env).address, tcp("env.lan", 6700));
+
+        filter.mpd_host = Some("config.lan".to_string());
+        assert_eq!(filter.mpd_target_with(env).address, tcp("config.lan", 6700));
+        filter.mpd_port = Some(6800);
+        assert_eq!(filter.mpd_target_with(env).address, tcp("config.lan", 6800));
+
+        // host:port 中的端口优先于 mpd_port 与 MPD_PORT
+        filter.mpd_host = Some("config.lan:6900".to_string());
+        assert_eq!(filter.mpd_target_with(env).address, tcp("config.lan", 6900));
+        let env_port = |key: &str| match key {
+            "MPD_HOST" => Some("env.lan:7000".to_string()),
+            "MPD_PORT" => Some("6700".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            PlayerFilter::default().mpd_target_with(env_port).address,
+            tcp("env.lan", 7000)
+        );
+
+        // 空的 MPD_HOST 与无效的 MPD_PORT 使用默认值
+        let empty = |key: &str| match key {
+            "MPD_HOST" => Some(String::new()),
+            "MPD_PORT" => Some("mpd".to_string()),
+            _ => None,
+        };
+        assert_eq!(
+            PlayerFilter::default().mpd_target_with(empty).address,
+            tcp("127.0.0.1", 6600)
+        );
+    }
+
+    fn player_filter(content: &str) -> PlayerFilter {
+        parse(&format!("[player-filter]\n{content}"))
+            .unwrap()
+            .player_filter
+    }
+
+    const FIREFOX: &str = "org.mpris.MediaPlayer2.firefox.instance_1_42";
+
+    #[test]
+    fn player_rules_ignore_case() {
+        let filter = player_filter(
+            "except = []\n[[player-filter.rules]]\naction = \"deny\"\nidentity = \"FireFox\"\nurl = 'YouTube\\.com$'\n",
+        );
+        let youtube = || Some("www.YOUTUBE.com".to_string());
+        assert!(!filter.allows(FIREFOX, "Mozilla Firefox", youtube));
+        assert!(filter.allows(FIREFOX, "Mozilla Firefox", || None));
+        assert!(filter.allows("org.mpris.MediaPlayer2.mpv", "mpv", youtube));
+    }
+
+    #[test]
+    fn first_matching_player_rule_wins() {
+        let filter = player_filter(
+            r#"
+[[player-filter.rules]]
+action = "allow"
+url = '^music\.youtube\.com$'
+
+[[player-filter.rules]]
+action = "deny"
+bus_name = 'firefox'
+
+[[player-filter.rules]]
+action = "allow"
+identity = 'firefox'
+"#,
+        );
+        let music = || Some("music.youtube.com".to_string());
+        let video = || Some("www.youtube.com".to_string());
+        assert!(filter.allows(FIREFOX, "Mozilla Firefox", music));
+        assert!(!filter.allows(FIREFOX, "Mozilla Firefox", video));
+    }
+
+    #[test]
+    fn player_rules_fall_back_to_only_and_except() {
+        let filter = player_filter(
+            r#"
+only = ["spotify", "browser"]
+except = ["browser"]
+
+[[player-filter.rules]]
+action = "allow"
+identity = 'chromium'
+"#,
+        );
+        let none = || None;
+        // 规则优先于 only / except
+        assert!(filter.allows("org.mpris.MediaPlayer2.chromium", "Chromium", none));
+        assert!(filter.allows("org.mpris.MediaPlayer2.spotify", "Spotify", none));
+        assert!(!filter.allows("org.mpris.MediaPlayer2.vlc", "VLC media player", none));
+        // except 优先于 only
+        assert!(!filter.allows(
+            "org.mpris.MediaPlayer2.plasma",
+            "Plasma Browser Integration",
+            none
+        ));
+    }
+}
@@ -32,6 +32,9 @@ pub enum LyricsError {
     #[error("No lyrics found")]
     NoLyricsFound,
 
+    #[error("Instrumental, no lyrics")]
+    Instrumental,
+
     #[error("JSON parse error")]
     JsonError,
 
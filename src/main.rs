@@ -1,10 +1,11 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use lyrics_next::client::get_lyrics_client;
 use lyrics_next::config::Config;
 use lyrics_next::log::init_logger;
+use lyrics_next::player::start_clock;
 use lyrics_next::ui::App;
 
 #[derive(Parser, Debug)]
@@ -12,6 +13,22 @@ use lyrics_next::ui::App;
 struct Args {
     #[arg(short, long)]
     config: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// 按内部时钟播放歌词文件，不连接播放器
+    Play {
+        /// 歌词文件，支持 LRC / SRT / VTT / TTML
+        #[arg(long)]
+        lrc: PathBuf,
+        /// 时长（秒），默认读取 [length:] 或最后一行
+        #[arg(long)]
+        duration: Option<f64>,
+    },
 }
 
 #[tokio::main]
@@ -19,6 +36,9 @@ async fn main() -> Result<()> {
     init_logger()?;
     let args = Args::parse();
     Config::load_or_default(args.config)?;
+    if let Some(Command::Play { lrc, duration }) = args.command {
+        start_clock(&lrc, duration).await?;
+    }
     get_lyrics_client();
     let mut app = App::default();
     app.run().await?;
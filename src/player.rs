@@ -1,15 +1,32 @@
+mod clock;
+mod cmus;
 mod mpd;
 mod mpris;
 
+use std::{
+    path::PathBuf,
+    sync::{OnceLock, RwLock},
+    time::Instant,
+};
+
+pub use clock::{ClockPlayer, start_clock};
+pub use cmus::CmusPlayer;
 pub use mpd::MpdPlayer;
 pub use mpris::MprisPlayer;
 
-use crate::error::LyricsError;
+use crate::{
+    config::{PlayerProtocol, get_config, pinned_player_path},
+    error::LyricsError,
+};
 
 #[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum TrackId {
     Mpris(String),
     Mpd(u32),
+    /// cmus 当前文件
+    Cmus(String),
+    /// ClockPlayer 播放的歌词文件
+    File(PathBuf),
     #[default]
     None,
 }
@@ -27,6 +44,8 @@ pub struct SongInfo {
     pub artist: String,
     pub album: String,
     pub duration: f64,
+    /// 播放器标识：MPRIS identity、MPD 地址或 cmus
+    pub player: String,
 }
 
 impl Default for SongInfo {
@@ -37,6 +56,7 @@ impl Default for SongInfo {
             artist: Default::default(),
             album: Default::default(),
             duration: Default::default(),
+            player: Default::default(),
         }
     }
 }
@@ -68,9 +88,142 @@ pub enum PlayerAction {
     Previous,
 }
 
+/// 播放状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    #[default]
+    Stopped,
+}
+
+/// 某一时刻采样得到的播放进度
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackState {
+    pub status: PlaybackStatus,
+    /// 采样时的位置（秒）
+    pub position: f64,
+    /// 播放速率，1.0 为正常速度
+    pub rate: f64,
+    pub sampled_at: Instant,
+}
+
+impl PlaybackState {
+    /// 推算当前位置，仅在播放中按速率前进
+    pub fn position_at(&self, now: Instant) -> f64 {
+        match self.status {
+            PlaybackStatus::Playing => {
+                self.position + now.duration_since(self.sampled_at).as_secs_f64() * self.rate
+            }
+            _ => self.position,
+        }
+    }
+}
+
+/// 在播放器列表中固定的播放器
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PinnedPlayer {
+    Mpd,
+    Cmus,
+    /// MPRIS 总线名称
+    Mpris(String),
+}
+
+impl PinnedPlayer {
+    fn parse(s: &str) -> Option<Self> {
+        match s.trim() {
+            "mpd" => Some(Self::Mpd),
+            "cmus" => Some(Self::Cmus),
+            s => s
+                .strip_prefix("mpris:")
+                .map(|bus_name| Self::Mpris(bus_name.to_string())),
+        }
+    }
+
+    fn serialize(&self) -> String {
+        match self {
+            Self::Mpd => "mpd".to_string(),
+            Self::Cmus => "cmus".to_string(),
+            Self::Mpris(bus_name) => format!("mpris:{}", bus_name),
+        }
+    }
+}
+
+/// 固定的播放器，启动时读取保存的选择
+fn pinned() -> &'static RwLock<Option<PinnedPlayer>> {
+    static PINNED: OnceLock<RwLock<Option<PinnedPlayer>>> = OnceLock::new();
+    PINNED.get_or_init(|| {
+        let saved = std::fs::read_to_string(pinned_player_path())
+            .ok()
+            .and_then(|s| PinnedPlayer::parse(&s));
+        RwLock::new(saved)
+    })
+}
+
+pub fn pinned_player() -> Option<PinnedPlayer> {
+    pinned().read().unwrap().clone()
+}
+
+/// 固定播放器，`persist` 为 true 时保存选择，取消固定时同时删除保存的选择
+pub fn pin_player(player: Option<PinnedPlayer>, persist: bool) -> Result<(), LyricsError> {
+    let path = pinned_player_path();
+    match &player {
+        Some(p) if persist => std::fs::write(path, p.serialize())?,
+        Some(_) => {}
+        None => {
+            std::fs::remove_file(path).ok();
+        }
+    }
+
+    *pinned().write().unwrap() = player;
+    mpris::reselect();
+    Ok(())
+}
+
+/// 播放器列表中的一项
+#[derive(Debug, Clone)]
+pub struct PlayerEntry {
+    pub pin: PinnedPlayer,
+    /// MPRIS identity、MPD 地址或 cmus socket 路径
+    pub name: String,
+    /// 未连接时为 None
+    pub status: Option<PlaybackStatus>,
+    pub song: Option<SongInfo>,
+}
+
+/// 列出所有 MPRIS 播放器、配置的 MPD 和运行中的 cmus
+///
+/// 探测均为阻塞调用，在阻塞线程中执行，不启动各协议的后台监听
+pub async fn list_players() -> Vec<PlayerEntry> {
+    let mpris = tokio::task::spawn_blocking(mpris::list);
+    let mpd = tokio::task::spawn_blocking(mpd::entry);
+    let cmus = tokio::task::spawn_blocking(cmus::entry);
+
+    let mut players = mpris.await.unwrap_or_default();
+    players.extend(mpd.await.ok());
+    players.extend(cmus.await.ok().flatten());
+    players
+}
+
+/// play 子命令优先，其次为固定的播放器，否则按配置选择
+fn protocol() -> PlayerProtocol {
+    if clock::running() {
+        return PlayerProtocol::Clock;
+    }
+    match pinned_player() {
+        Some(PinnedPlayer::Mpd) => PlayerProtocol::Mpd,
+        Some(PinnedPlayer::Cmus) => PlayerProtocol::Cmus,
+        Some(PinnedPlayer::Mpris(_)) => PlayerProtocol::Mpris,
+        None => get_config().read().unwrap().player_filter.protocol,
+    }
+}
+
 pub trait Player {
     fn get_current_song(&self) -> impl std::future::Future<Output = Result<SongInfo, LyricsError>>;
     fn get_position(&self) -> impl std::future::Future<Output = Result<f64, LyricsError>>;
+    fn get_playback_state(
+        &self,
+    ) -> impl std::future::Future<Output = Result<PlaybackState, LyricsError>>;
     fn player_action(
         &self,
         action: PlayerAction,
@@ -79,67 +232,77 @@ pub trait Player {
 }
 
 pub async fn get_current_song() -> Result<SongInfo, LyricsError> {
-    let protocol = {
-        let config = crate::config::get_config().read().unwrap();
-        config.player_filter.protocol
-    };
-
-    match protocol {
-        crate::config::PlayerProtocol::Auto => {
-            // 优先尝试 MPD
-            match MpdPlayer.get_current_song().await {
-                Ok(song) => Ok(song),
-                Err(_) => {
-                    // MPD 失败，回退到 MPRIS
-                    MprisPlayer.get_current_song().await
-                }
+    match protocol() {
+        PlayerProtocol::Auto => {
+            // 依次尝试 MPD、cmus，最后回退到 MPRIS
+            if let Ok(song) = MpdPlayer.get_current_song().await {
+                return Ok(song);
             }
+            if let Ok(song) = CmusPlayer.get_current_song().await {
+                return Ok(song);
+            }
+            MprisPlayer.get_current_song().await
         }
-        crate::config::PlayerProtocol::Mpd => MpdPlayer.get_current_song().await,
-        crate::config::PlayerProtocol::Mpris => MprisPlayer.get_current_song().await,
+        PlayerProtocol::Mpd => MpdPlayer.get_current_song().await,
+        PlayerProtocol::Mpris => MprisPlayer.get_current_song().await,
+        PlayerProtocol::Cmus => CmusPlayer.get_current_song().await,
+        PlayerProtocol::Clock => ClockPlayer.get_current_song().await,
     }
 }
 
 pub async fn get_position() -> Result<f64, LyricsError> {
-    let protocol = {
-        let config = crate::config::get_config().read().unwrap();
-        config.player_filter.protocol
-    };
-
-    match protocol {
-        crate::config::PlayerProtocol::Auto => {
-            // 优先尝试 MPD
-            match MpdPlayer.get_position().await {
-                Ok(pos) => Ok(pos),
-                Err(_) => {
-                    // MPD 失败，回退到 MPRIS
-                    MprisPlayer.get_position().await
-                }
+    match protocol() {
+        PlayerProtocol::Auto => {
+            // 依次尝试 MPD、cmus，最后回退到 MPRIS
+            if let Ok(pos) = MpdPlayer.get_position().await {
+                return Ok(pos);
+            }
+            if let Ok(pos) = CmusPlayer.get_position().await {
+                return Ok(pos);
+            }
+            MprisPlayer.get_position().await
+        }
+        PlayerProtocol::Mpd => MpdPlayer.get_position().await,
+        PlayerProtocol::Mpris => MprisPlayer.get_position().await,
+        PlayerProtocol::Cmus => CmusPlayer.get_position().await,
+        PlayerProtocol::Clock => ClockPlayer.get_position().await,
+    }
+}
+
+pub async fn get_playback_state() -> Result<PlaybackState, LyricsError> {
+    match protocol() {
+        PlayerProtocol::Auto => {
+            // 依次尝试 MPD、cmus，最后回退到 MPRIS
+            if let Ok(state) = MpdPlayer.get_playback_state().await {
+                return Ok(state);
+            }
+            if let Ok(state) = CmusPlayer.get_playback_state().await {
+                return Ok(state);
             }
+            MprisPlayer.get_playback_state().await
         }
-        crate::config::PlayerProtocol::Mpd => MpdPlayer.get_position().await,
-        crate::config::PlayerProtocol::Mpris => MprisPlayer.get_position().await,
+        PlayerProtocol::Mpd => MpdPlayer.get_playback_state().await,
+        PlayerProtocol::Mpris => MprisPlayer.get_playback_state().await,
+        PlayerProtocol::Cmus => CmusPlayer.get_playback_state().await,
+        PlayerProtocol::Clock => ClockPlayer.get_playback_state().await,
     }
 }
 
 pub async fn player_action(action: PlayerAction, song: &SongInfo) -> Result<(), LyricsError> {
-    let protocol = {
-        let config = crate::config::get_config().read().unwrap();
-        config.player_filter.protocol
-    };
-
-    match protocol {
-        crate::config::PlayerProtocol::Auto => {
-            // 优先尝试 MPD
-            match MpdPlayer.player_action(action.clone(), song).await {
-                Ok(()) => Ok(()),
-                Err(_) => {
-                    // MPD 失败，回退到 MPRIS
-                    MprisPlayer.player_action(action, song).await
-                }
+    match protocol() {
+        PlayerProtocol::Auto => {
+            // 依次尝试 MPD、cmus，最后回退到 MPRIS
+            if MpdPlayer.player_action(action.clone(), song).await.is_ok() {
+                return Ok(());
+            }
+            if CmusPlayer.player_action(action.clone(), song).await.is_ok() {
+                return Ok(());
             }
+            MprisPlayer.player_action(action, song).await
         }
-        crate::config::PlayerProtocol::Mpd => MpdPlayer.player_action(action, song).await,
-        crate::config::PlayerProtocol::Mpris => MprisPlayer.player_action(action, song).await,
+        PlayerProtocol::Mpd => MpdPlayer.player_action(action, song).await,
+        PlayerProtocol::Mpris => MprisPlayer.player_action(action, song).await,
+        PlayerProtocol::Cmus => CmusPlayer.player_action(action, song).await,
+        PlayerProtocol::Clock => ClockPlayer.player_action(action, song).await,
     }
 }
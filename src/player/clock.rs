@@ -0,0 +1,229 @@
+//! 离线播放：按内部时钟播放歌词文件，不依赖任何播放器。
+//! 用于卡拉 OK 练习以及在没有 MPD / D-Bus 的环境下演示界面。
+
+use std::{path::Path, sync::RwLock, time::Instant};
+
+use crate::{error::LyricsError, song::LyricParser};
+
+use super::{PlaybackState, PlaybackStatus, Player, PlayerAction, SongInfo, TrackId};
+
+pub struct ClockPlayer;
+
+/// 快进快退的秒数
+const SEEK_STEP: f64 = 5.0;
+/// 未指定时长且歌词没有 `[length:]` 时，最后一行之后保留的时间
+const TAIL: f64 = 5.0;
+
+#[derive(Debug, Clone)]
+struct Clock {
+    song: SongInfo,
+    state: PlaybackState,
+}
+
+static CLOCK: RwLock<Option<Clock>> = RwLock::new(None);
+
+/// 开始播放歌词文件，时长依次取 `duration`、`[length:]`、最后一行之后 5 秒
+pub async fn start_clock(path: &Path, duration: Option<f64>) -> Result<(), LyricsError> {
+    let doc = tokio::fs::read_to_string(path).await?;
+    let lyrics = LyricParser::parse(doc, duration.unwrap_or(0.0)).await?;
+
+    let duration = duration.or(lyrics.meta.length).unwrap_or_else(|| {
+        lyrics
+            .lines
+            .last()
+            .map(|l| l.timestamp_start.max(l.timestamp_end) + TAIL)
+            .unwrap_or(TAIL)
+    });
+    let title = lyrics.meta.title.clone().unwrap_or_else(|| {
+        path.file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    });
+
+    let song = SongInfo {
+        track_id: TrackId::File(path.to_path_buf()),
+        title,
+        artist: lyrics.meta.artist.clone().unwrap_or_default(),
+        album: lyrics.meta.album.clone().unwrap_or_default(),
+        duration,
+        player: "clock".to_string(),
+    };
+
+    *CLOCK.write().unwrap() = Some(Clock::new(song, Instant::now()));
+    Ok(())
+}
+
+/// 是否由 play 子命令启动了时钟
+pub(super) fn running() -> bool {
+    CLOCK.read().unwrap().is_some()
+}
+
+fn state_at(status: PlaybackStatus, position: f64, now: Instant) -> PlaybackState {
+    PlaybackState {
+        status,
+        position,
+        rate: 1.0,
+        sampled_at: now,
+    }
+}
+
+impl Clock {
+    fn new(song: SongInfo, now: Instant) -> Self {
+        Clock {
+            song,
+            state: state_at(PlaybackStatus::Playing, 0.0, now),
+        }
+    }
+
+    /// 播放到结尾时停止
+    fn tick(&mut self, now: Instant) {
+        if self.state.status == PlaybackStatus::Playing
+            && self.state.position_at(now) >= self.song.duration
+        {
+            self.state = state_at(PlaybackStatus::Stopped, self.song.duration, now);
+        }
+    }
+
+    /// 只有一首歌：上一首从头播放，下一首跳到结尾
+    fn apply(&mut self, action: PlayerAction, now: Instant) {
+        self.tick(now);
+        let position = self.state.position_at(now);
+
+        self.state = match action {
+            PlayerAction::Toggle => match self.state.status {
+                PlaybackStatus::Playing => state_at(PlaybackStatus::Paused, position, now),
+                PlaybackStatus::Paused => state_at(PlaybackStatus::Playing, position, now),
+                // 播放结束后重新开始
+                PlaybackStatus::Stopped => state_at(PlaybackStatus::Playing, 0.0, now),
+            },
+            PlayerAction::Left | PlayerAction::Right => {
+                let delta = match action {
+                    PlayerAction::Left => -SEEK_STEP,
+                    _ => SEEK_STEP,
+                };
+                // 结束后拖动进度时转为暂停，便于继续播放
+                let status = match self.state.status {
+                    PlaybackStatus::Stopped => PlaybackStatus::Paused,
+                    status => status,
+                };
+                let position = (position + delta).clamp(0.0, self.song.duration);
+                state_at(status, position, now)
+            }
+            PlayerAction::Previous => state_at(PlaybackStatus::Playing, 0.0, now),
+            PlayerAction::Next => state_at(PlaybackStatus::Stopped, self.song.duration, now),
+        };
+    }
+}
+
+/// 读取时钟，播放到结尾时停止
+fn current() -> Result<Clock, LyricsError> {
+    let mut clock = CLOCK.write().unwrap();
+    let clock = clock.as_mut().ok_or(LyricsError::NoPlayerFound)?;
+    clock.tick(Instant::now());
+    Ok(clock.clone())
+}
+
+impl Player for ClockPlayer {
+    async fn get_current_song(&self) -> Result<SongInfo, LyricsError> {
+        Ok(current()?.song)
+    }
+
+    async fn get_position(&self) -> Result<f64, LyricsError> {
+        Ok(current()?.state.position_at(Instant::now()))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState, LyricsError> {
+        Ok(current()?.state)
+    }
+
+    async fn player_action(
+        &self,
+        action: PlayerAction,
+        _song: &SongInfo,
+    ) -> Result<(), LyricsError> {
+        let mut clock = CLOCK.write().unwrap();
+        let clock = clock.as_mut().ok_or(LyricsError::NoPlayerFound)?;
+        clock.apply(action, Instant::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn clock(duration: f64, start: Instant) -> Clock {
+        let song = SongInfo {
+            duration,
+            ..Default::default()
+        };
+        Clock::new(song, start)
+    }
+
+    fn at(start: Instant, secs: f64) -> Instant {
+        start + Duration::from_secs_f64(secs)
+    }
+
+    #[test]
+    fn toggle_pauses_and_resumes() {
+        let start = Instant::now();
+        let mut clock = clock(60.0, start);
+
+        clock.apply(PlayerAction::Toggle, at(start, 10.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Paused);
+        assert_eq!(clock.state.position_at(at(start, 20.0)), 10.0);
+
+        clock.apply(PlayerAction::Toggle, at(start, 20.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Playing);
+        assert_eq!(clock.state.position_at(at(start, 22.0)), 12.0);
+    }
+
+    #[test]
+    fn seek_is_clamped_to_song() {
+        let start = Instant::now();
+        let mut clock = clock(60.0, start);
+
+        clock.apply(PlayerAction::Right, at(start, 3.0));
+        assert_eq!(clock.state.position, 8.0);
+        clock.apply(PlayerAction::Left, at(start, 3.0));
+        clock.apply(PlayerAction::Left, at(start, 3.0));
+        assert_eq!(clock.state.position, 0.0);
+        assert_eq!(clock.state.status, PlaybackStatus::Playing);
+
+        // 暂停在 56 秒，快进超出结尾
+        clock.apply(PlayerAction::Toggle, at(start, 59.0));
+        clock.apply(PlayerAction::Right, at(start, 59.0));
+        assert_eq!(clock.state.position, 60.0);
+        assert_eq!(clock.state.status, PlaybackStatus::Paused);
+    }
+
+    #[test]
+    fn stops_at_end_and_restarts() {
+        let start = Instant::now();
+        let mut clock = clock(60.0, start);
+
+        clock.tick(at(start, 59.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Playing);
+        clock.tick(at(start, 61.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Stopped);
+        assert_eq!(clock.state.position_at(at(start, 70.0)), 60.0);
+
+        // 结束后播放从头开始
+        clock.apply(PlayerAction::Toggle, at(start, 70.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Playing);
+        assert_eq!(clock.state.position_at(at(start, 71.0)), 1.0);
+
+        // 结束后拖动进度转为暂停
+        clock.apply(PlayerAction::Next, at(start, 72.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Stopped);
+        clock.apply(PlayerAction::Left, at(start, 72.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Paused);
+        assert_eq!(clock.state.position, 55.0);
+
+        clock.apply(PlayerAction::Previous, at(start, 80.0));
+        assert_eq!(clock.state.status, PlaybackStatus::Playing);
+        assert_eq!(clock.state.position_at(at(start, 80.0)), 0.0);
+    }
+}
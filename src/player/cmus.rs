@@ -0,0 +1,407 @@
+//! cmus 远程控制协议，与 cmus-remote 相同：
+//! 通过 Unix socket 发送一行命令，响应以空行结束。
+
+use std::{
+    collections::HashMap,
+    io::{BufRead, BufReader, Write},
+    os::unix::net::UnixStream,
+    path::Path,
+    sync::{
+        Once, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Notify;
+use tracing::debug;
+
+use crate::{config::get_config, error::LyricsError};
+
+use super::{
+    PinnedPlayer, PlaybackState, PlaybackStatus, Player, PlayerAction, PlayerEntry, SongInfo,
+    TrackId,
+};
+
+pub struct CmusPlayer;
+
+const SOCKET_TIMEOUT: Duration = Duration::from_millis(500);
+/// cmus 没有变化通知，后台按该间隔读取 `status`
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+/// 重连等待时间，失败后翻倍；本地 socket 重连开销小，上限较短
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(5);
+/// 首次读取时等待后台连接的时间
+const FIRST_CONNECT_WAIT: Duration = Duration::from_millis(500);
+
+/// 后台连接维护的最新状态，断开时为 None
+#[derive(Debug, Clone)]
+struct Snapshot {
+    song: Option<SongInfo>,
+    state: PlaybackState,
+}
+
+static SNAPSHOT: RwLock<Option<Snapshot>> = RwLock::new(None);
+static WATCHER: Once = Once::new();
+/// 后台线程是否已完成第一次连接尝试
+static ATTEMPTED: AtomicBool = AtomicBool::new(false);
+static ATTEMPT: Notify = Notify::const_new();
+
+/// `status` 命令的输出
+#[derive(Debug, Default)]
+struct Status {
+    status: PlaybackStatus,
+    /// 本地文件路径或流地址，停止时为空
+    file: Option<String>,
+    duration: f64,
+    position: f64,
+    tags: HashMap<String, String>,
+}
+
+fn socket_path() -> std::path::PathBuf {
+    get_config()
+        .read()
+        .unwrap()
+        .player_filter
+        .cmus_socket_path()
+}
+
+/// 与 cmus 的连接，可连续发送多条命令
+struct Connection {
+    reader: BufReader<UnixStream>,
+    writer: UnixStream,
+}
+
+impl Connection {
+    fn open() -> Result<Self, LyricsError> {
+        let stream = UnixStream::connect(socket_path())?;
+        stream.set_read_timeout(Some(SOCKET_TIMEOUT))?;
+        Ok(Self {
+            writer: stream.try_clone()?,
+            reader: BufReader::new(stream),
+        })
+    }
+
+    /// 发送命令并读取到空行为止
+    fn request(&mut self, command: &str) -> Result<String, LyricsError> {
+        self.writer.write_all(format!("{}\n", command).as_bytes())?;
+
+        let mut response = String::new();
+        loop {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            if line == "\n" {
+                break;
+            }
+            response.push_str(&line);
+        }
+        Ok(response)
+    }
+}
+
+/// 读取后台连接的状态，首次调用时启动后台线程并等待第一次连接
+async fn snapshot() -> Result<Snapshot, LyricsError> {
+    // 先创建等待，避免错过后台线程的通知
+    let attempted = ATTEMPT.notified();
+    WATCHER.call_once(|| {
+        if let Err(e) = thread::Builder::new().name("cmus-poll".into()).spawn(watch) {
+            debug!("cmus 后台线程启动失败: {}", e);
+            ATTEMPTED.store(true, Ordering::Release);
+        }
+    });
+    if !ATTEMPTED.load(Ordering::Acquire) {
+        tokio::time::timeout(FIRST_CONNECT_WAIT, attempted)
+            .await
+            .ok();
+    }
+
+    SNAPSHOT
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or(LyricsError::NoPlayerFound)
+}
+
+/// 通知等待第一次连接的调用方
+fn attempted() {
+    ATTEMPTED.store(true, Ordering::Release);
+    ATTEMPT.notify_waiters();
+}
+
+/// 保持连接，断开后按退避时间重连
+fn watch() {
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        match Connection::open() {
+            Ok(mut connection) => {
+                backoff = RECONNECT_MIN;
+                if let Err(e) = follow(&mut connection) {
+                    debug!("cmus 连接断开: {}", e);
+                }
+            }
+            Err(e) => debug!("cmus 连接失败: {}", e),
+        }
+
+        *SNAPSHOT.write().unwrap() = None;
+        attempted();
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(RECONNECT_MAX);
+    }
+}
+
+/// 定时读取状态
+fn follow(connection: &mut Connection) -> Result<(), LyricsError> {
+    let mut last = None;
+    loop {
+        let status = parse_status(&connection.request("status")?);
+        let state = playback_state(&status, Instant::now(), &mut last);
+        *SNAPSHOT.write().unwrap() = Some(Snapshot {
+            song: song_info(&status),
+            state,
+        });
+        attempted();
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+fn parse_status(response: &str) -> Status {
+    let mut status = Status::default();
+
+    for line in response.lines() {
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "status" => {
+                status.status = match value {
+                    "playing" => PlaybackStatus::Playing,
+                    "paused" => PlaybackStatus::Paused,
+                    _ => PlaybackStatus::Stopped,
+                }
+            }
+            "file" | "url" => status.file = Some(value.to_string()),
+            // 流媒体的时长为 -1
+            "duration" => status.duration = value.parse::<f64>().unwrap_or(0.0).max(0.0),
+            "position" => status.position = value.parse::<f64>().unwrap_or(0.0).max(0.0),
+            "tag" => {
+                if let Some((tag, value)) = value.split_once(' ') {
+                    status.tags.insert(tag.to_string(), value.to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    status
+}
+
+fn song_info(status: &Status) -> Option<SongInfo> {
+    let file = status.file.clone()?;
+    let tag = |key: &str| status.tags.get(key).cloned();
+
+    // 没有标签时使用文件名作为标题
+    let title = tag("title").unwrap_or_else(|| {
+        Path::new(&file)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| file.clone())
+    });
+    let artist = tag("artist")
+        .or_else(|| tag("albumartist"))
+        .unwrap_or_default();
+
+    Some(SongInfo {
+        track_id: TrackId::Cmus(file),
+        title,
+        artist,
+        album: tag("album").unwrap_or_default(),
+        duration: status.duration,
+        player: "cmus".to_string(),
+    })
+}
+
+/// cmus 只报告整秒位置：整秒位置与推算一致时沿用上次的采样，变化时以新的整秒为准
+fn playback_state(
+    status: &Status,
+    sampled_at: Instant,
+    last: &mut Option<(String, PlaybackState)>,
+) -> PlaybackState {
+    let sampled = PlaybackState {
+        status: status.status,
+        position: status.position,
+        rate: 1.0,
+        sampled_at,
+    };
+    let file = status.file.clone().unwrap_or_default();
+
+    let mut state = sampled;
+    if let Some((last_file, last_state)) = last.as_ref()
+        && *last_file == file
+    {
+        let predicted = last_state.position_at(sampled_at);
+        if (sampled.position..sampled.position + 1.0).contains(&predicted) {
+            if last_state.status == sampled.status {
+                return *last_state;
+            }
+            // 播放状态变化时保留推算的亚秒位置
+            state.position = predicted;
+        }
+    }
+
+    *last = Some((file, state));
+    state
+}
+
+/// socket 存在时列出 cmus，单独连接读取一次状态，不启动后台轮询
+pub(super) fn entry() -> Option<PlayerEntry> {
+    let path = socket_path();
+    if !path.exists() {
+        return None;
+    }
+    let status = Connection::open()
+        .and_then(|mut connection| connection.request("status"))
+        .map(|response| parse_status(&response))
+        .ok();
+
+    Some(PlayerEntry {
+        pin: PinnedPlayer::Cmus,
+        name: path.display().to_string(),
+        status: status.as_ref().map(|s| s.status),
+        song: status.as_ref().and_then(song_info),
+    })
+}
+
+impl Player for CmusPlayer {
+    async fn get_current_song(&self) -> Result<SongInfo, LyricsError> {
+        snapshot().await?.song.ok_or(LyricsError::NoPlayerFound)
+    }
+
+    async fn get_position(&self) -> Result<f64, LyricsError> {
+        Ok(snapshot().await?.state.position_at(Instant::now()))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState, LyricsError> {
+        Ok(snapshot().await?.state)
+    }
+
+    /// 控制命令单独建立连接，在阻塞线程中发送
+    async fn player_action(
+        &self,
+        action: PlayerAction,
+        _song: &SongInfo,
+    ) -> Result<(), LyricsError> {
+        let command = match action {
+            // player-pause 在播放与暂停之间切换
+            PlayerAction::Toggle => "player-pause",
+            PlayerAction::Left => "seek -5",
+            PlayerAction::Right => "seek +5",
+            PlayerAction::Next => "player-next",
+            PlayerAction::Previous => "player-prev",
+        };
+        debug!("cmus 命令: {}", command);
+
+        tokio::task::spawn_blocking(move || Connection::open()?.request(command))
+            .await
+            .map_err(std::io::Error::other)??;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{os::unix::net::UnixListener, sync::mpsc};
+
+    use super::*;
+
+    /// 在临时 socket 上应答 `status`，其它命令转发给测试
+    fn fake_cmus() -> (std::path::PathBuf, mpsc::Receiver<String>) {
+        let path = std::env::temp_dir().join(format!("lyrics-next-cmus-{}", std::process::id()));
+        std::fs::remove_file(&path).ok();
+        let listener = UnixListener::bind(&path).unwrap();
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let stream = stream.unwrap();
+                let sender = sender.clone();
+                thread::spawn(move || {
+                    let mut writer = stream.try_clone().unwrap();
+                    for command in BufReader::new(stream).lines() {
+                        let Ok(command) = command else { break };
+                        let response = match command.as_str() {
+                            "status" => {
+                                "status playing\nfile /music/a.flac\nduration 200\nposition 42\ntag title Song\ntag artist A\ntag album B\n\n"
+                            }
+                            _ => {
+                                sender.send(command).ok();
+                                "\n"
+                            }
+                        };
+                        if writer.write_all(response.as_bytes()).is_err() {
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        (path, receiver)
+    }
+
+    #[tokio::test]
+    async fn reads_status_and_sends_commands() {
+        let (path, commands) = fake_cmus();
+        get_config().write().unwrap().player_filter.cmus_socket = Some(path.display().to_string());
+
+        // 列出播放器时只探测一次，不启动后台轮询
+        let entry = entry().unwrap();
+        assert_eq!(entry.status, Some(PlaybackStatus::Playing));
+        assert_eq!(entry.song.unwrap().title, "Song");
+        assert!(!ATTEMPTED.load(Ordering::Acquire));
+
+        let song = CmusPlayer.get_current_song().await.unwrap();
+        assert_eq!(song.title, "Song");
+        assert_eq!(song.artist, "A");
+        assert_eq!(song.album, "B");
+        assert_eq!(song.track_id, TrackId::Cmus("/music/a.flac".to_string()));
+        let state = CmusPlayer.get_playback_state().await.unwrap();
+        assert_eq!(state.status, PlaybackStatus::Playing);
+        assert!(state.position >= 42.0);
+
+        CmusPlayer
+            .player_action(PlayerAction::Toggle, &song)
+            .await
+            .unwrap();
+        let command = commands.recv_timeout(Duration::from_secs(1)).unwrap();
+        assert_eq!(command, "player-pause");
+
+        std::fs::remove_file(path).ok();
+    }
+
+    #[test]
+    fn keeps_sub_second_position_within_same_second() {
+        let status = Status {
+            status: PlaybackStatus::Playing,
+            file: Some("a.flac".to_string()),
+            position: 10.0,
+            ..Default::default()
+        };
+        let start = Instant::now();
+        let mut last = None;
+        let first = playback_state(&status, start, &mut last);
+
+        // 0.5 秒后 cmus 仍报告 10 秒，沿用第一次采样
+        let later = playback_state(&status, start + Duration::from_millis(500), &mut last);
+        assert_eq!(later.sampled_at, first.sampled_at);
+        assert!((later.position_at(start + Duration::from_millis(500)) - 10.5).abs() < 1e-6);
+
+        // 整秒变化后以新的整秒为准
+        let status = Status {
+            position: 12.0,
+            ..status
+        };
+        let jumped = playback_state(&status, start + Duration::from_millis(800), &mut last);
+        assert_eq!(jumped.position, 12.0);
+    }
+}
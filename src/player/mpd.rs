@@ -1,58 +1,312 @@
-use mpd::Client;
-use std::time::Duration;
+use mpd::{Client, Idle, Subsystem};
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    os::unix::net::UnixStream,
+    sync::{
+        Once, RwLock,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use tokio::sync::Notify;
 use tracing::debug;
 
-use crate::{config::get_config, error::LyricsError};
+use crate::{
+    config::{MpdAddress, get_config},
+    error::LyricsError,
+};
 
-use super::{Player, PlayerAction, SongInfo, TrackId};
+use super::{
+    PinnedPlayer, PlaybackState, PlaybackStatus, Player, PlayerAction, PlayerEntry, SongInfo,
+    TrackId,
+};
 
 pub struct MpdPlayer;
 
-fn get_client() -> Result<Client, LyricsError> {
-    let config = get_config().read().unwrap();
-    let host = &config.player_filter.mpd_host;
-    let port = config.player_filter.mpd_port;
+/// TCP 或 Unix socket 连接
+enum MpdStream {
+    Tcp(TcpStream),
+    Unix(UnixStream),
+}
+
+impl Read for MpdStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.read(buf),
+            Self::Unix(s) => s.read(buf),
+        }
+    }
+}
 
-    let addr = format!("{}:{}", host, port);
-    debug!("连接到 MPD: {}", addr);
+impl Write for MpdStream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Tcp(s) => s.write(buf),
+            Self::Unix(s) => s.write(buf),
+        }
+    }
 
-    let client = Client::connect(&addr)?;
-    Ok(client)
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Tcp(s) => s.flush(),
+            Self::Unix(s) => s.flush(),
+        }
+    }
 }
 
-impl Player for MpdPlayer {
-    async fn get_current_song(&self) -> Result<SongInfo, LyricsError> {
-        let mut client = get_client()?;
-        let song = client.currentsong()?.ok_or(LyricsError::NoPlayerFound)?;
+impl MpdStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        match self {
+            Self::Tcp(s) => s.try_clone().map(Self::Tcp),
+            Self::Unix(s) => s.try_clone().map(Self::Unix),
+        }
+    }
+
+    /// 读取超时作用于整个 socket，克隆的句柄同样生效
+    fn set_read_timeout(&self, timeout: Duration) -> io::Result<()> {
+        match self {
+            Self::Tcp(s) => s.set_read_timeout(Some(timeout)),
+            Self::Unix(s) => s.set_read_timeout(Some(timeout)),
+        }
+    }
+}
+
+type MpdClient = Client<MpdStream>;
+
+/// 重连等待时间，失败后翻倍
+const RECONNECT_MIN: Duration = Duration::from_secs(1);
+const RECONNECT_MAX: Duration = Duration::from_secs(30);
+/// 首次读取时等待后台连接的时间
+const FIRST_CONNECT_WAIT: Duration = Duration::from_millis(500);
+/// 命令的读取超时
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+/// idle 期间没有变化时，每隔该时间结束 idle 并 ping，以发现静默断开的连接
+const KEEPALIVE: Duration = Duration::from_secs(15);
+
+/// 后台连接维护的最新状态，断开时为 None
+#[derive(Debug, Clone)]
+struct Snapshot {
+    song: Option<SongInfo>,
+    state: PlaybackState,
+}
+
+static SNAPSHOT: RwLock<Option<Snapshot>> = RwLock::new(None);
+static WATCHER: Once = Once::new();
+/// 后台线程是否已完成第一次连接尝试
+static ATTEMPTED: AtomicBool = AtomicBool::new(false);
+static ATTEMPT: Notify = Notify::const_new();
 
-        let track_id = song.place.map(|p| TrackId::Mpd(p.id.0)).unwrap_or_default();
+/// 读取后台连接的状态，首次调用时启动后台线程并等待第一次连接
+async fn snapshot() -> Result<Snapshot, LyricsError> {
+    // 先创建等待，避免错过后台线程的通知
+    let attempted = ATTEMPT.notified();
+    WATCHER.call_once(|| {
+        if let Err(e) = thread::Builder::new().name("mpd-idle".into()).spawn(watch) {
+            debug!("MPD 后台线程启动失败: {}", e);
+            ATTEMPTED.store(true, Ordering::Release);
+        }
+    });
+    if !ATTEMPTED.load(Ordering::Acquire) {
+        tokio::time::timeout(FIRST_CONNECT_WAIT, attempted)
+            .await
+            .ok();
+    }
+
+    SNAPSHOT
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or(LyricsError::NoPlayerFound)
+}
+
+/// 通知等待第一次连接的调用方
+fn attempted() {
+    ATTEMPTED.store(true, Ordering::Release);
+    ATTEMPT.notify_waiters();
+}
 
-        let title = song.title.unwrap_or_default();
-        let artist = song.artist.unwrap_or_default();
-        let album = song
-            .tags
-            .iter()
-            .find(|(k, _)| k == "Album")
-            .map(|(_, v)| v.clone())
-            .unwrap_or_default();
-        let duration = song.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+/// 配置的 MPD，单独连接读取一次状态，不启动后台连接；未连接时状态为空
+pub(super) fn entry() -> PlayerEntry {
+    let name = get_config()
+        .read()
+        .unwrap()
+        .player_filter
+        .mpd_target()
+        .to_string();
+    let snapshot = get_client().and_then(|mut client| sample(&mut client)).ok();
 
-        Ok(SongInfo {
-            track_id,
-            title,
-            artist,
-            album,
-            duration,
-        })
+    PlayerEntry {
+        pin: PinnedPlayer::Mpd,
+        name,
+        status: snapshot.as_ref().map(|s| s.state.status),
+        song: snapshot.and_then(|s| s.song),
+    }
+}
+
+/// 保持长连接，断开后按退避时间重连
+fn watch() {
+    let mut backoff = RECONNECT_MIN;
+    loop {
+        match connect() {
+            Ok((mut client, mut socket)) => {
+                backoff = RECONNECT_MIN;
+                if let Err(e) = follow(&mut client, &mut socket) {
+                    debug!("MPD 连接断开: {}", e);
+                }
+            }
+            Err(e) => debug!("MPD 连接失败: {}", e),
+        }
+
+        *SNAPSHOT.write().unwrap() = None;
+        attempted();
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(RECONNECT_MAX);
+    }
+}
+
+/// 读取状态后通过 `idle player` 等待下一次变化，超时则保活后重新读取
+fn follow(client: &mut MpdClient, socket: &mut MpdStream) -> Result<(), LyricsError> {
+    loop {
+        let snapshot = sample(client)?;
+        *SNAPSHOT.write().unwrap() = Some(snapshot);
+        attempted();
+
+        socket.set_read_timeout(KEEPALIVE)?;
+        let changed = client.idle(&[Subsystem::Player])?.get();
+        socket.set_read_timeout(READ_TIMEOUT)?;
+
+        match changed {
+            Ok(_) => {}
+            Err(mpd::error::Error::Io(e))
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                keepalive(client, socket)?
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// 结束 idle 并 ping，连接已断开时返回错误
+///
+/// idle 超时时客户端缓冲区为空，`noidle` 的响应直接从 socket 读取。
+fn keepalive(client: &mut MpdClient, socket: &mut MpdStream) -> Result<(), LyricsError> {
+    socket.write_all(b"noidle\n")?;
+
+    // 响应为 `OK`，期间恰好有变化时前面还有 `changed: player`
+    let mut line = Vec::new();
+    let mut byte = [0];
+    loop {
+        if socket.read(&mut byte)? == 0 {
+            return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+        }
+        match byte[0] {
+            b'\n' if line == b"OK" => break,
+            b'\n' if line.starts_with(b"ACK") => {
+                return Err(io::Error::other(String::from_utf8_lossy(&line).to_string()).into());
+            }
+            b'\n' => line.clear(),
+            b => line.push(b),
+        }
+    }
+
+    client.ping()?;
+    Ok(())
+}
+
+fn sample(client: &mut MpdClient) -> Result<Snapshot, LyricsError> {
+    let song = client.currentsong()?.map(song_info);
+    let status = client.status()?;
+    let sampled_at = Instant::now();
+
+    let status_kind = match status.state {
+        mpd::State::Play => PlaybackStatus::Playing,
+        mpd::State::Pause => PlaybackStatus::Paused,
+        mpd::State::Stop => PlaybackStatus::Stopped,
+    };
+
+    Ok(Snapshot {
+        song,
+        state: PlaybackState {
+            status: status_kind,
+            position: status.elapsed.map(|d| d.as_secs_f64()).unwrap_or(0.0),
+            rate: 1.0,
+            sampled_at,
+        },
+    })
+}
+
+fn song_info(song: mpd::Song) -> SongInfo {
+    let track_id = song.place.map(|p| TrackId::Mpd(p.id.0)).unwrap_or_default();
+
+    let title = song.title.unwrap_or_default();
+    let artist = song.artist.unwrap_or_default();
+    let album = song
+        .tags
+        .iter()
+        .find(|(k, _)| k == "Album")
+        .map(|(_, v)| v.clone())
+        .unwrap_or_default();
+    let duration = song.duration.map(|d| d.as_secs_f64()).unwrap_or(0.0);
+    let player = get_config()
+        .read()
+        .unwrap()
+        .player_filter
+        .mpd_target()
+        .to_string();
+
+    SongInfo {
+        track_id,
+        title,
+        artist,
+        album,
+        duration,
+        player,
+    }
+}
+
+/// 建立连接，同时返回 socket 句柄用于调整读取超时
+fn connect() -> Result<(MpdClient, MpdStream), LyricsError> {
+    let target = get_config().read().unwrap().player_filter.mpd_target();
+    debug!("连接到 MPD: {}", target);
+
+    let stream = match &target.address {
+        MpdAddress::Tcp(host, port) => MpdStream::Tcp(TcpStream::connect((host.as_str(), *port))?),
+        MpdAddress::Unix(path) => MpdStream::Unix(UnixStream::connect(path)?),
+    };
+    stream.set_read_timeout(READ_TIMEOUT)?;
+    let socket = stream.try_clone()?;
+
+    let mut client = Client::new(stream)?;
+    if let Some(password) = &target.password {
+        client.login(password)?;
+    }
+    Ok((client, socket))
+}
+
+fn get_client() -> Result<MpdClient, LyricsError> {
+    connect().map(|(client, _)| client)
+}
+
+impl Player for MpdPlayer {
+    async fn get_current_song(&self) -> Result<SongInfo, LyricsError> {
+        snapshot().await?.song.ok_or(LyricsError::NoPlayerFound)
     }
 
     async fn get_position(&self) -> Result<f64, LyricsError> {
-        let mut client = get_client()?;
-        let status = client.status()?;
-        let pos = status.elapsed.map(|d| d.as_secs_f64()).unwrap_or(0.0);
-        Ok(pos)
+        Ok(snapshot().await?.state.position_at(Instant::now()))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState, LyricsError> {
+        Ok(snapshot().await?.state)
     }
 
+    /// 控制命令较少，单独建立连接，避免打断 idle
     async fn player_action(
         &self,
         action: PlayerAction,
@@ -98,3 +352,76 @@ impl Player for MpdPlayer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader},
+        net::TcpListener,
+    };
+
+    use super::*;
+
+    /// 按顺序接受连接，每个连接播放一首歌，收到 `idle` 后断开
+    fn fake_mpd(titles: &'static [&'static str]) -> u16 {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+
+        thread::spawn(move || {
+            for (id, title) in titles.iter().enumerate() {
+                let (stream, _) = listener.accept().unwrap();
+                let mut writer = stream.try_clone().unwrap();
+                writer.write_all(b"OK MPD 0.23.5\n").unwrap();
+
+                for command in BufReader::new(stream).lines() {
+                    let response = match command.unwrap().as_str() {
+                        "currentsong" => format!(
+                            "file: {title}.flac\nTitle: {title}\nArtist: A\nTime: 200\nduration: 200.000\nPos: 0\nId: {id}\nOK\n"
+                        ),
+                        // status 以命令列表发送
+                        "command_list_begin" | "status" | "replay_gain_status" => continue,
+                        "command_list_end" => format!(
+                            "state: play\nsongid: {id}\nelapsed: 12.500\nreplay_gain_mode: off\nOK\n"
+                        ),
+                        // idle 一段时间后断开，模拟 MPD 重启
+                        _ => {
+                            thread::sleep(Duration::from_millis(300));
+                            break;
+                        }
+                    };
+                    writer.write_all(response.as_bytes()).unwrap();
+                }
+            }
+        });
+        port
+    }
+
+    #[tokio::test]
+    async fn reconnects_after_server_restart() {
+        let port = fake_mpd(&["first", "second"]);
+        {
+            let mut config = get_config().write().unwrap();
+            config.player_filter.mpd_host = Some("127.0.0.1".to_string());
+            config.player_filter.mpd_port = Some(port);
+        }
+
+        let song = MpdPlayer.get_current_song().await.unwrap();
+        assert_eq!(song.title, "first");
+        let state = MpdPlayer.get_playback_state().await.unwrap();
+        assert_eq!(state.status, PlaybackStatus::Playing);
+        assert!(state.position >= 12.5);
+
+        // 断开后按退避时间重连，读到第二个连接的歌曲
+        let deadline = Instant::now() + RECONNECT_MIN * 4;
+        loop {
+            if let Ok(song) = MpdPlayer.get_current_song().await
+                && song.title == "second"
+            {
+                assert_eq!(song.track_id, TrackId::Mpd(1));
+                break;
+            }
+            assert!(Instant::now() < deadline, "did not reconnect");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+}
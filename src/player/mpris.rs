@@ -1,70 +1,331 @@
-use std::time::Duration;
+use std::{
+    cell::RefCell,
+    rc::Rc,
+    sync::{
+        Once, OnceLock, RwLock,
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use anyhow::Context;
-use mpris::{Player as MprisClient, PlayerFinder, TrackID};
+use dbus::{
+    Message, arg::PropMap, blocking::LocalConnection, channel::Channel, ffidisp::Connection,
+    message::MatchRule,
+};
+use mpris::{PlaybackStatus as MprisStatus, Player as MprisClient, PlayerFinder, TrackID};
+use tracing::debug;
 
 use crate::{config::get_config, error::LyricsError};
 
-use super::{Player, PlayerAction, SongInfo, TrackId};
+use super::{
+    PinnedPlayer, PlaybackState, PlaybackStatus, Player, PlayerAction, PlayerEntry, SongInfo,
+    TrackId, pinned_player,
+};
 
 pub struct MprisPlayer;
 
-fn is_valid_player(player: &MprisClient) -> bool {
-    let identity = player.identity().to_lowercase();
+const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const MPRIS_PATH: &str = "/org/mpris/MediaPlayer2";
+const DBUS_TIMEOUT_MS: i32 = 500;
+
+/// 没有信号时的刷新间隔，用于校正不发送 Seeked 的播放器
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+/// 等待信号时检查固定播放器变化的间隔
+const RESELECT_POLL: Duration = Duration::from_millis(200);
+/// 定期重建连接，丢弃 mpris 连接中积压的信号
+const RESCAN_INTERVAL: Duration = Duration::from_secs(60);
+/// 会话总线不可用时的重试等待，失败后翻倍
+const RETRY_MIN: Duration = Duration::from_secs(1);
+const RETRY_MAX: Duration = Duration::from_secs(30);
+/// 首次读取时等待后台扫描的时间
+const FIRST_SCAN_WAIT: Duration = Duration::from_millis(500);
+
+/// 后台监听维护的选中播放器状态，没有可用播放器时为 None
+#[derive(Debug, Clone)]
+struct Snapshot {
+    bus_name: String,
+    song: Option<SongInfo>,
+    state: PlaybackState,
+}
 
+static SNAPSHOT: RwLock<Option<Snapshot>> = RwLock::new(None);
+static WATCHER: Once = Once::new();
+/// 固定的播放器变化，需要重新选择
+static RESELECT: AtomicBool = AtomicBool::new(false);
+
+/// 指定的总线地址，未设置时连接会话总线
+static BUS_ADDRESS: OnceLock<String> = OnceLock::new();
+
+/// 总线信号，None 表示有播放器出现或退出，Some 为播放状态变化的发送方
+type Signals = Rc<RefCell<Vec<Option<String>>>>;
+
+fn is_valid_player(player: &MprisClient) -> bool {
     let config = &get_config().read().unwrap().player_filter;
+    config.allows(player.bus_name(), player.identity(), || {
+        player
+            .get_metadata()
+            .ok()
+            .and_then(|m| m.url().and_then(url_host))
+    })
+}
+
+fn url_host(url: &str) -> Option<String> {
+    reqwest::Url::parse(url)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+}
 
-    if !config.except.is_empty() && config.except.iter().any(|k| identity.contains(k)) {
-        return false;
+/// 控制与查询使用的连接
+fn connect() -> Result<Connection, dbus::Error> {
+    match BUS_ADDRESS.get() {
+        Some(address) => {
+            let connection = Connection::open_private(address)?;
+            connection.register()?;
+            Ok(connection)
+        }
+        None => Connection::new_session(),
     }
+}
 
-    if !config.only.is_empty() {
-        return config.only.iter().any(|k| identity.contains(k));
+/// 监听信号使用的连接
+fn connect_local() -> Result<LocalConnection, dbus::Error> {
+    match BUS_ADDRESS.get() {
+        Some(address) => {
+            let mut channel = Channel::open_private(address)?;
+            channel.register()?;
+            Ok(channel.into())
+        }
+        None => LocalConnection::new_session(),
     }
+}
 
-    true
+/// 读取后台监听的状态，首次调用时启动后台线程
+fn snapshot() -> Result<Snapshot, LyricsError> {
+    WATCHER.call_once(|| {
+        let (ready, first) = mpsc::channel();
+        let spawned = thread::Builder::new()
+            .name("mpris-watch".into())
+            .spawn(move || watch(ready));
+        if spawned.is_ok() {
+            first.recv_timeout(FIRST_SCAN_WAIT).ok();
+        }
+    });
+
+    SNAPSHOT
+        .read()
+        .unwrap()
+        .clone()
+        .ok_or(LyricsError::NoPlayerFound)
+}
+
+/// 通知后台线程重新选择播放器
+pub(super) fn reselect() {
+    RESELECT.store(true, Ordering::Relaxed);
+}
+
+/// 列出总线上所有播放器，不做过滤
+pub(super) fn list() -> Vec<PlayerEntry> {
+    let Ok(connection) = connect() else {
+        return Vec::new();
+    };
+    PlayerFinder::for_connection(connection)
+        .find_all()
+        .unwrap_or_default()
+        .iter()
+        .map(|p| PlayerEntry {
+            pin: PinnedPlayer::Mpris(p.bus_name().to_string()),
+            name: p.identity().to_string(),
+            status: p.get_playback_status().ok().map(playback_status),
+            song: song_info(p),
+        })
+        .collect()
 }
 
-async fn get_player() -> Result<MprisClient, LyricsError> {
-    let player_finder = PlayerFinder::new()?;
-    let player = player_finder
-        .find_all()?
+fn watch(ready: mpsc::Sender<()>) {
+    let mut backoff = RETRY_MIN;
+    loop {
+        if let Err(e) = follow(&ready) {
+            debug!("MPRIS 监听失败: {}", e);
+        }
+
+        *SNAPSHOT.write().unwrap() = None;
+        ready.send(()).ok();
+        thread::sleep(backoff);
+        backoff = (backoff * 2).min(RETRY_MAX);
+    }
+}
+
+/// 监听 PropertiesChanged / Seeked / NameOwnerChanged，有变化时更新状态
+fn follow(ready: &mpsc::Sender<()>) -> Result<(), LyricsError> {
+    let bus = connect_local().map_err(mpris::DBusError::from)?;
+    let signals: Signals = Default::default();
+
+    let s = signals.clone();
+    bus.add_match(
+        MatchRule::new_signal("org.freedesktop.DBus", "NameOwnerChanged"),
+        move |(name, _, _): (String, String, String), _: &LocalConnection, _: &Message| {
+            if name.starts_with(MPRIS_PREFIX) {
+                s.borrow_mut().push(None);
+            }
+            true
+        },
+    )
+    .map_err(mpris::DBusError::from)?;
+
+    // 播放状态变化可能改变选择结果，记录发送方
+    let s = signals.clone();
+    bus.add_match(
+        MatchRule::new_signal("org.freedesktop.DBus.Properties", "PropertiesChanged")
+            .with_path(MPRIS_PATH),
+        move |(_, changed, _): (String, PropMap, Vec<String>),
+              _: &LocalConnection,
+              msg: &Message| {
+            if changed.contains_key("PlaybackStatus") {
+                s.borrow_mut().push(msg.sender().map(|b| b.to_string()));
+            }
+            true
+        },
+    )
+    .map_err(mpris::DBusError::from)?;
+
+    // 其余信号只用于唤醒，重新读取选中的播放器
+    bus.add_match(
+        MatchRule::new_signal("org.mpris.MediaPlayer2.Player", "Seeked").with_path(MPRIS_PATH),
+        |_: (), _: &LocalConnection, _: &Message| true,
+    )
+    .map_err(mpris::DBusError::from)?;
+
+    let mut player: Option<MprisClient> = None;
+    let mut rescan = true;
+    let mut scanned_at = Instant::now();
+    loop {
+        if rescan || scanned_at.elapsed() >= RESCAN_INTERVAL {
+            player = select_player();
+            scanned_at = Instant::now();
+        }
+
+        let snapshot = player.as_ref().and_then(|p| sample(p).ok());
+        // 选中的播放器读取失败时下一轮重新选择
+        rescan = snapshot.is_none();
+        *SNAPSHOT.write().unwrap() = snapshot;
+        ready.send(()).ok();
+
+        wait_signals(&bus)?;
+
+        // 播放器增减或其他播放器的播放状态变化时重新选择
+        let unique_name = player.as_ref().map(|p| p.unique_name().to_string());
+        rescan |= RESELECT.swap(false, Ordering::Relaxed);
+        rescan |= signals
+            .borrow_mut()
+            .drain(..)
+            .any(|sender| sender.is_none() || sender != unique_name);
+    }
+}
+
+/// 等待总线信号，超时或固定的播放器变化时返回
+fn wait_signals(bus: &LocalConnection) -> Result<(), LyricsError> {
+    let deadline = Instant::now() + REFRESH_INTERVAL;
+    while Instant::now() < deadline && !RESELECT.load(Ordering::Relaxed) {
+        if bus.process(RESELECT_POLL).map_err(mpris::DBusError::from)? {
+            break;
+        }
+    }
+    while bus
+        .process(Duration::ZERO)
+        .map_err(mpris::DBusError::from)?
+    {}
+    Ok(())
+}
+
+/// 固定的播放器优先，否则选择正在播放的，其次是暂停的
+fn select_player() -> Option<MprisClient> {
+    let players = PlayerFinder::for_connection(connect().ok()?)
+        .find_all()
+        .ok()?;
+    if let Some(PinnedPlayer::Mpris(bus_name)) = pinned_player() {
+        return players.into_iter().find(|p| p.bus_name() == bus_name);
+    }
+
+    players
         .into_iter()
         .filter(is_valid_player)
-        .max_by_key(|p| p.is_running())
-        .ok_or_else(|| LyricsError::NoPlayerFound)?;
+        .max_by_key(|p| match p.get_playback_status() {
+            Ok(MprisStatus::Playing) => 2,
+            Ok(MprisStatus::Paused) => 1,
+            _ => 0,
+        })
+}
+
+fn playback_status(status: MprisStatus) -> PlaybackStatus {
+    match status {
+        MprisStatus::Playing => PlaybackStatus::Playing,
+        MprisStatus::Paused => PlaybackStatus::Paused,
+        MprisStatus::Stopped => PlaybackStatus::Stopped,
+    }
+}
+
+fn sample(player: &MprisClient) -> Result<Snapshot, LyricsError> {
+    let status = playback_status(player.get_playback_status()?);
+    let position = player.get_position().map(|d| d.as_secs_f64())?;
+    let sampled_at = Instant::now();
+    // Rate 为可选属性，不支持时按正常速度
+    let rate = player.get_playback_rate().unwrap_or(1.0);
+
+    Ok(Snapshot {
+        bus_name: player.bus_name().to_string(),
+        song: song_info(player),
+        state: PlaybackState {
+            status,
+            position,
+            rate,
+            sampled_at,
+        },
+    })
+}
+
+fn song_info(player: &MprisClient) -> Option<SongInfo> {
+    let metadata = player.get_metadata().ok()?;
+
+    let track_id = metadata
+        .track_id()
+        .map(|tid| TrackId::Mpris(tid.to_string()))
+        .unwrap_or_default();
+
+    let title = metadata.title()?.to_string();
+    let artist = metadata.artists().map(|a| a.join(", "))?;
+    let album = metadata.album_name().unwrap_or_default().to_string();
+    let duration = metadata.length().map(|d| d.as_secs_f64()).unwrap_or(0.0);
 
-    Ok(player)
+    Some(SongInfo {
+        track_id,
+        title,
+        artist,
+        album,
+        duration,
+        player: player.identity().to_string(),
+    })
+}
+
+/// 控制命令直接连接到选中的播放器
+fn get_player() -> Result<MprisClient, LyricsError> {
+    let bus_name = snapshot()?.bus_name;
+    let connection = connect().map_err(mpris::DBusError::from)?;
+    Ok(MprisClient::new(connection, bus_name, DBUS_TIMEOUT_MS)?)
 }
 
 impl Player for MprisPlayer {
     async fn get_current_song(&self) -> Result<SongInfo, LyricsError> {
-        let player = get_player().await?;
-        let metadata = player.get_metadata()?;
-
-        let track_id = metadata
-            .track_id()
-            .map(|tid| TrackId::Mpris(tid.to_string()))
-            .unwrap_or_default();
-
-        let title = metadata.title().context("无标题")?.to_string();
-        let artist = metadata.artists().map(|a| a.join(", ")).context("无作家")?;
-        let album = metadata.album_name().unwrap_or_default().to_string();
-        let duration = metadata.length().map(|d| d.as_secs_f64()).unwrap_or(0.0);
-
-        Ok(SongInfo {
-            track_id,
-            title,
-            artist,
-            album,
-            duration,
-        })
+        snapshot()?.song.ok_or(LyricsError::NoPlayerFound)
     }
 
     async fn get_position(&self) -> Result<f64, LyricsError> {
-        let player = get_player().await?;
-        let pos = player.get_position().map(|d| d.as_secs_f64())?;
-        Ok(pos)
+        Ok(snapshot()?.state.position_at(Instant::now()))
+    }
+
+    async fn get_playback_state(&self) -> Result<PlaybackState, LyricsError> {
+        Ok(snapshot()?.state)
     }
 
     async fn player_action(
@@ -72,7 +333,7 @@ impl Player for MprisPlayer {
         action: PlayerAction,
         song: &SongInfo,
     ) -> Result<(), LyricsError> {
-        let player = get_player().await?;
+        let player = get_player()?;
 
         match action {
             PlayerAction::Toggle => player.play_pause()?,
@@ -81,7 +342,7 @@ impl Player for MprisPlayer {
                     return Ok(());
                 }
                 let add = Duration::from_secs(5);
-                let pos = player.get_position()? - add;
+                let pos = player.get_position()?.saturating_sub(add);
                 if let TrackId::Mpris(ref tid_str) = song.track_id {
                     let track_id =
                         TrackID::new(tid_str.clone()).map_err(|e| anyhow::anyhow!("{}", e))?;
@@ -107,3 +368,253 @@ impl Player for MprisPlayer {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        collections::HashMap,
+        io::{BufRead, BufReader},
+        process::{Child, Command, Stdio},
+        sync::{Arc, Mutex, atomic::AtomicUsize},
+    };
+
+    use dbus::{
+        arg::{RefArg, Variant},
+        blocking::Connection as BlockingConnection,
+        channel::{MatchingReceiver, Sender},
+    };
+
+    use super::*;
+
+    /// 私有的总线，结束时关闭
+    struct PrivateBus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl PrivateBus {
+        fn start() -> Self {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--print-address", "--nofork"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("dbus-daemon");
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+            Self {
+                daemon,
+                address: address.trim().to_string(),
+            }
+        }
+
+        fn connect(&self) -> BlockingConnection {
+            let mut channel = Channel::open_private(&self.address).unwrap();
+            channel.register().unwrap();
+            channel.into()
+        }
+    }
+
+    impl Drop for PrivateBus {
+        fn drop(&mut self) {
+            self.daemon.kill().ok();
+        }
+    }
+
+    enum Emit {
+        Status(&'static str),
+        Seeked,
+        Volume,
+    }
+
+    /// 只应答 Properties.Get 的播放器，记录 Identity 的读取次数
+    struct FakePlayer {
+        identity_reads: Arc<AtomicUsize>,
+        emit: mpsc::Sender<Emit>,
+    }
+
+    impl FakePlayer {
+        fn start(bus: &PrivateBus, name: &'static str, status: &'static str) -> Self {
+            let identity_reads = Arc::new(AtomicUsize::new(0));
+            let (emit, signals) = mpsc::channel();
+            let (ready, started) = mpsc::channel();
+
+            let reads = identity_reads.clone();
+            let conn = bus.connect();
+            thread::spawn(move || {
+                let status = Arc::new(Mutex::new(status));
+
+                let s = status.clone();
+                conn.start_receive(
+                    MatchRule::new_method_call(),
+                    Box::new(move |msg, conn| {
+                        let value = match msg.read2::<String, String>() {
+                            Ok((_, property)) if msg.member().as_deref() == Some("Get") => {
+                                property_value(&property, name, *s.lock().unwrap(), &reads)
+                            }
+                            _ => None,
+                        };
+                        let reply = match value {
+                            Some(value) => msg.method_return().append1(Variant(value)),
+                            None => msg.error(
+                                &"org.freedesktop.DBus.Error.UnknownProperty".into(),
+                                c"unknown",
+                            ),
+                        };
+                        conn.send(reply).ok();
+                        true
+                    }),
+                );
+                conn.request_name(format!("{MPRIS_PREFIX}{name}"), false, true, false)
+                    .unwrap();
+                ready.send(()).unwrap();
+
+                loop {
+                    // 总线关闭后退出
+                    if conn.process(Duration::from_millis(20)).is_err() {
+                        break;
+                    }
+                    while let Ok(signal) = signals.try_recv() {
+                        let msg = match signal {
+                            Emit::Status(value) => {
+                                *status.lock().unwrap() = value;
+                                properties_changed("PlaybackStatus", Box::new(value.to_string()))
+                            }
+                            Emit::Volume => properties_changed("Volume", Box::new(0.5)),
+                            Emit::Seeked => Message::new_signal(
+                                MPRIS_PATH,
+                                "org.mpris.MediaPlayer2.Player",
+                                "Seeked",
+                            )
+                            .unwrap()
+                            .append1(30_000_000i64),
+                        };
+                        conn.send(msg).ok();
+                    }
+                }
+            });
+
+            started.recv().unwrap();
+            Self {
+                identity_reads,
+                emit,
+            }
+        }
+
+        fn emit(&self, signal: Emit) {
+            self.emit.send(signal).unwrap();
+        }
+
+        fn identity_reads(&self) -> usize {
+            self.identity_reads.load(Ordering::SeqCst)
+        }
+    }
+
+    fn property_value(
+        property: &str,
+        name: &str,
+        status: &str,
+        identity_reads: &AtomicUsize,
+    ) -> Option<Box<dyn RefArg>> {
+        Some(match property {
+            "Identity" => {
+                identity_reads.fetch_add(1, Ordering::SeqCst);
+                Box::new(format!("Fake {name}"))
+            }
+            "PlaybackStatus" => Box::new(status.to_string()),
+            "Position" => Box::new(12_000_000i64),
+            "Rate" => Box::new(1.0),
+            "Metadata" => {
+                let mut metadata: PropMap = HashMap::new();
+                metadata.insert(
+                    "mpris:trackid".into(),
+                    Variant(Box::new(dbus::Path::from("/track/1"))),
+                );
+                metadata.insert("xesam:title".into(), Variant(Box::new(name.to_string())));
+                metadata.insert(
+                    "xesam:artist".into(),
+                    Variant(Box::new(vec!["A".to_string()])),
+                );
+                metadata.insert("mpris:length".into(), Variant(Box::new(200_000_000i64)));
+                Box::new(metadata)
+            }
+            _ => return None,
+        })
+    }
+
+    fn properties_changed(property: &str, value: Box<dyn RefArg>) -> Message {
+        let mut changed: PropMap = HashMap::new();
+        changed.insert(property.to_string(), Variant(value));
+        Message::new_signal(
+            MPRIS_PATH,
+            "org.freedesktop.DBus.Properties",
+            "PropertiesChanged",
+        )
+        .unwrap()
+        .append3(
+            "org.mpris.MediaPlayer2.Player",
+            changed,
+            Vec::<String>::new(),
+        )
+    }
+
+    /// 等待条件成立
+    async fn wait_until(what: &str, mut condition: impl AsyncFnMut() -> bool) {
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while !condition().await {
+            assert!(Instant::now() < deadline, "timed out waiting for {what}");
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    }
+
+    #[tokio::test]
+    #[ignore = "requires dbus-daemon"]
+    async fn rescans_only_on_owner_or_status_changes() {
+        let bus = PrivateBus::start();
+        BUS_ADDRESS.set(bus.address.clone()).unwrap();
+
+        let first = FakePlayer::start(&bus, "first", "Playing");
+        wait_until("first player", async || {
+            MprisPlayer
+                .get_current_song()
+                .await
+                .is_ok_and(|s| s.title == "first")
+        })
+        .await;
+
+        // 选中播放器的状态变化直接反映到快照
+        first.emit(Emit::Status("Paused"));
+        wait_until("paused", async || {
+            MprisPlayer
+                .get_playback_state()
+                .await
+                .is_ok_and(|s| s.status == PlaybackStatus::Paused)
+        })
+        .await;
+
+        // 新播放器出现时重新扫描
+        let second = FakePlayer::start(&bus, "second", "Stopped");
+        wait_until("rescan", async || second.identity_reads() > 0).await;
+
+        // 其他播放器的进度和属性变化不触发扫描
+        let reads = second.identity_reads();
+        second.emit(Emit::Seeked);
+        second.emit(Emit::Volume);
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(second.identity_reads(), reads);
+        let song = MprisPlayer.get_current_song().await.unwrap();
+        assert_eq!(song.title, "first");
+
+        // 其他播放器开始播放时切换
+        second.emit(Emit::Status("Playing"));
+        wait_until("second player", async || {
+            MprisPlayer
+                .get_current_song()
+                .await
+                .is_ok_and(|s| s.title == "second")
+        })
+        .await;
+    }
+}
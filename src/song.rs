@@ -1,82 +1,637 @@
-use std::time::Instant;
+use std::{sync::OnceLock, time::Instant};
+
+use regex::Regex;
 
 use crate::error::LyricsError;
 
+mod srt;
+mod ttml;
+mod vtt;
+
+pub use srt::SrtWriter;
+pub use ttml::TtmlWriter;
+pub use vtt::VttWriter;
+
 pub use crate::config::PlayerProtocol;
 pub use crate::player::{
-    PlayerAction, SongInfo, TrackId, get_current_song, get_position, player_action,
+    PlaybackState, PlaybackStatus, PlayerAction, SongInfo, TrackId, get_current_song,
+    get_playback_state, get_position, player_action,
 };
 
 #[derive(Debug, Clone, PartialEq, Default)]
 pub struct PlayTime {
     pub current_time: f64,
-    pub last_valid_pos: Option<(Instant, f64)>,
+    /// 最近一次成功获取的播放状态
+    pub last_state: Option<PlaybackState>,
+    /// 输出设备延迟（秒），从播放器位置中扣除
+    pub latency: f64,
 }
 
 pub async fn get_current_time_song(st: PlayTime) -> Result<PlayTime, LyricsError> {
     let mut st = st;
 
-    match get_position().await {
-        Ok(pos) => {
-            st.current_time = pos;
-            st.last_valid_pos = Some((Instant::now(), pos));
-        }
-        Err(_) => {
-            if let Some((time, pos)) = st.last_valid_pos {
-                let delta = Instant::now().duration_since(time).as_secs_f64();
-                st.current_time = pos + delta;
-            }
-        }
+    // 获取失败时按上次状态推算，暂停时保持不动
+    if let Ok(state) = get_playback_state().await {
+        st.last_state = Some(state);
+    }
+    if let Some(state) = st.last_state {
+        st.current_time = state.position_at(Instant::now()) - st.latency;
     }
 
     Ok(st)
 }
 
-#[derive(Debug, Clone)]
+/// 逐字时间片段
+#[derive(Debug, Clone, PartialEq)]
+pub struct LyricsWord {
+    pub timestamp_start: f64,
+    pub timestamp_end: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub struct LyricsLine {
     pub timestamp_start: f64,
     pub timestamp_end: f64,
     pub text: String,
+    /// 逐字时间（增强 LRC），普通 LRC 为空
+    pub words: Vec<LyricsWord>,
+    /// 翻译
+    pub translation: Option<String>,
+    /// 罗马音
+    pub romanization: Option<String>,
+}
+
+/// LRC 头部标签 `[ti:] [ar:] [al:] [by:] [length:] [offset:]`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LyricsMeta {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub by: Option<String>,
+    /// 歌曲时长（秒）
+    pub length: Option<f64>,
+    /// 时间偏移（毫秒），正数表示歌词提前显示
+    pub offset: i64,
+}
+
+impl LyricsMeta {
+    fn set_tag(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if value.is_empty() {
+            return;
+        }
+
+        match key.to_lowercase().as_str() {
+            "ti" => self.title = Some(value.to_string()),
+            "ar" => self.artist = Some(value.to_string()),
+            "al" => self.album = Some(value.to_string()),
+            "by" => self.by = Some(value.to_string()),
+            "length" => self.length = Self::parse_length(value),
+            "offset" => self.offset = value.trim_start_matches('+').parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+
+    /// `mm:ss` 或 `mm:ss.xx`
+    fn parse_length(value: &str) -> Option<f64> {
+        let (minutes, seconds) = value.split_once(':')?;
+        let minutes = minutes.trim().parse::<f64>().ok()?;
+        let seconds = seconds.trim().parse::<f64>().ok()?;
+        Some(minutes * 60.0 + seconds)
+    }
+}
+
+/// 解析后的歌词文档
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Lyrics {
+    pub meta: LyricsMeta,
+    pub lines: Vec<LyricsLine>,
+    /// 没有时间标签的纯文本歌词
+    pub unsynced: bool,
+}
+
+impl Lyrics {
+    /// 翻译与原文时间允许的误差（秒）
+    const MERGE_TOLERANCE: f64 = 1.0;
+
+    /// 按时间戳将翻译合并到最接近的原文行
+    pub fn merge_translation(&mut self, translation: &Lyrics) {
+        self.merge(translation, |line, text| line.translation = text);
+    }
+
+    /// 按时间戳将罗马音合并到最接近的原文行
+    pub fn merge_romanization(&mut self, romanization: &Lyrics) {
+        self.merge(romanization, |line, text| line.romanization = text);
+    }
+
+    fn merge(&mut self, other: &Lyrics, set: impl Fn(&mut LyricsLine, Option<String>)) {
+        for line in self.lines.iter_mut() {
+            // 空行与 `//` 表示该行没有翻译
+            let text = other
+                .lines
+                .iter()
+                .map(|t| ((t.timestamp_start - line.timestamp_start).abs(), t))
+                .filter(|(delta, _)| *delta <= Self::MERGE_TOLERANCE)
+                .min_by(|a, b| a.0.total_cmp(&b.0))
+                .map(|(_, t)| t.text.clone())
+                .filter(|text| !text.is_empty() && text != "//");
+            set(line, text);
+        }
+    }
+
+    /// 调整 offset（毫秒），正数使歌词提前
+    pub fn shift_offset(&mut self, delta: i64) {
+        if self.unsynced || delta == 0 {
+            return;
+        }
+        self.meta.offset += delta;
+
+        // 不做截断，保证写回时可还原原始时间；最后一行仍以歌曲结束为止
+        let shift = delta as f64 / 1000.0;
+        let last = self.lines.len().saturating_sub(1);
+        for (i, line) in self.lines.iter_mut().enumerate() {
+            line.timestamp_start -= shift;
+            if i != last {
+                line.timestamp_end -= shift;
+            }
+            for word in line.words.iter_mut() {
+                word.timestamp_start -= shift;
+                word.timestamp_end -= shift;
+            }
+        }
+    }
+
+    pub fn has_translation(&self) -> bool {
+        self.lines.iter().any(|l| l.translation.is_some())
+    }
+
+    pub fn has_romanization(&self) -> bool {
+        self.lines.iter().any(|l| l.romanization.is_some())
+    }
+}
+
+/// 下载或缓存的原始歌词文本
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct LyricsDoc {
+    /// 原文
+    pub lyric: String,
+    /// 翻译（LRC）
+    pub translation: Option<String>,
+    /// 罗马音（LRC）
+    pub romanization: Option<String>,
+}
+
+impl LyricsDoc {
+    pub fn new(lyric: String) -> Self {
+        Self {
+            lyric,
+            ..Default::default()
+        }
+    }
 }
 
+/// 毫秒转换为 LRC 时间 `mm:ss.xx`，非整 10 毫秒时保留三位
+pub fn format_timestamp(ms: u64) -> String {
+    let (minutes, seconds, millis) = (ms / 60_000, ms / 1000 % 60, ms % 1000);
+    match millis % 10 {
+        0 => format!("{:02}:{:02}.{:02}", minutes, seconds, millis / 10),
+        _ => format!("{:02}:{:02}.{:03}", minutes, seconds, millis),
+    }
+}
+
+/// 由逐字时间（毫秒，绝对时间）生成增强 LRC 行
+pub fn enhanced_lrc_line(start: u64, words: &[(u64, u64, String)]) -> String {
+    let mut line = format!("[{}]", format_timestamp(start));
+    let mut end: Option<u64> = None;
+    for (word_start, duration, text) in words {
+        // 字间有停顿时补充上一个字的结束标签
+        if let Some(end) = end
+            && end < *word_start
+        {
+            line.push_str(&format!("<{}>", format_timestamp(end)));
+        }
+        line.push_str(&format!("<{}>{}", format_timestamp(*word_start), text));
+        end = Some(word_start + duration);
+    }
+    if let Some(end) = end {
+        line.push_str(&format!("<{}>", format_timestamp(end)));
+    }
+    line
+}
+
+/// 输出规范化的 LRC，解析后可得到相同的歌词行
+pub struct LrcWriter;
+
+impl LrcWriter {
+    /// 原文，包含头部标签与逐字时间，相同文本的行合并为 `[t1][t2]text`
+    pub fn write(lyrics: &Lyrics) -> String {
+        let mut out = Self::header(&lyrics.meta);
+
+        if lyrics.unsynced {
+            for line in &lyrics.lines {
+                out.push_str(&line.text);
+                out.push('\n');
+            }
+            return out;
+        }
+
+        let stamp = Self::stamper(&lyrics.meta);
+        let mut written = vec![false; lyrics.lines.len()];
+        for (i, line) in lyrics.lines.iter().enumerate() {
+            if written[i] {
+                continue;
+            }
+
+            if !line.words.is_empty() {
+                let words: Vec<(u64, u64, String)> = line
+                    .words
+                    .iter()
+                    .map(|w| {
+                        let start = stamp(w.timestamp_start);
+                        (
+                            start,
+                            stamp(w.timestamp_end).saturating_sub(start),
+                            w.text.clone(),
+                        )
+                    })
+                    .collect();
+                out.push_str(&enhanced_lrc_line(stamp(line.timestamp_start), &words));
+                out.push('\n');
+                continue;
+            }
+
+            for (j, other) in lyrics.lines.iter().enumerate().skip(i) {
+                if !written[j] && other.words.is_empty() && other.text == line.text {
+                    written[j] = true;
+                    out.push_str(&format!(
+                        "[{}]",
+                        format_timestamp(stamp(other.timestamp_start))
+                    ));
+                }
+            }
+            out.push_str(&line.text);
+            out.push('\n');
+        }
+
+        out
+    }
+
+    /// 翻译与罗马音，按原文行时间输出，供缓存为独立文件
+    pub fn write_doc(lyrics: &Lyrics) -> LyricsDoc {
+        let sidecar = |text: fn(&LyricsLine) -> Option<&String>| {
+            if lyrics.unsynced || !lyrics.lines.iter().any(|l| text(l).is_some()) {
+                return None;
+            }
+
+            let stamp = Self::stamper(&lyrics.meta);
+            let mut out = Self::header(&LyricsMeta {
+                offset: lyrics.meta.offset,
+                ..Default::default()
+            });
+            for line in &lyrics.lines {
+                out.push_str(&format!(
+                    "[{}]{}\n",
+                    format_timestamp(stamp(line.timestamp_start)),
+                    text(line).map_or("//", |t| t.as_str())
+                ));
+            }
+            Some(out)
+        };
+
+        LyricsDoc {
+            lyric: Self::write(lyrics),
+            translation: sidecar(|l| l.translation.as_ref()),
+            romanization: sidecar(|l| l.romanization.as_ref()),
+        }
+    }
+
+    fn header(meta: &LyricsMeta) -> String {
+        let mut out = String::new();
+        for (key, value) in [
+            ("ti", &meta.title),
+            ("ar", &meta.artist),
+            ("al", &meta.album),
+            ("by", &meta.by),
+        ] {
+            if let Some(value) = value {
+                out.push_str(&format!("[{key}:{value}]\n"));
+            }
+        }
+        if let Some(length) = meta.length {
+            let length = format_timestamp((length * 1000.0).round() as u64);
+            out.push_str(&format!("[length:{length}]\n"));
+        }
+        if meta.offset != 0 {
+            out.push_str(&format!("[offset:{}]\n", meta.offset));
+        }
+        out
+    }
+
+    /// 解析时已扣除 offset，输出时加回以保留原始时间
+    fn stamper(meta: &LyricsMeta) -> impl Fn(f64) -> u64 {
+        let offset = meta.offset as f64 / 1000.0;
+        move |t: f64| ((t + offset) * 1000.0).round().max(0.0) as u64
+    }
+}
+
+/// 秒转换为 `hh:mm:ss.mmm`，SRT 使用 `,` 分隔毫秒
+fn format_clock(seconds: f64, separator: char) -> String {
+    let ms = (seconds.max(0.0) * 1000.0).round() as u64;
+    format!(
+        "{:02}:{:02}:{:02}{}{:03}",
+        ms / 3_600_000,
+        ms / 60_000 % 60,
+        ms / 1000 % 60,
+        separator,
+        ms % 1000
+    )
+}
+
+/// 解析 `hh:mm:ss.mmm` / `mm:ss.mmm` / `ss.mmm`，毫秒分隔符可为 `.` 或 `,`
+fn parse_clock(s: &str) -> Option<f64> {
+    let s = s.trim().replace(',', ".");
+    let mut seconds = 0.0;
+    for part in s.split(':') {
+        seconds = seconds * 60.0 + part.parse::<f64>().ok()?;
+    }
+    Some(seconds)
+}
+
+/// 字幕类格式（SRT/VTT）按空行切分为块
+fn cue_blocks(doc: &str) -> Vec<Vec<&str>> {
+    let mut blocks = Vec::new();
+    let mut block = Vec::new();
+    for line in doc.trim_start_matches('\u{feff}').lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+        } else {
+            block.push(line);
+        }
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    blocks
+}
+
+/// `start --> end [settings]`
+fn parse_cue_timing(line: &str) -> Option<(f64, f64)> {
+    let (start, end) = line.split_once("-->")?;
+    let end = end.split_whitespace().next()?;
+    Some((parse_clock(start)?, parse_clock(end)?))
+}
+
+/// 去除 `<i>` `<font ...>` 等格式标签
+fn strip_tags(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut in_tag = false;
+    for c in text.chars() {
+        match c {
+            '<' => in_tag = true,
+            '>' if in_tag => in_tag = false,
+            _ if !in_tag => out.push(c),
+            _ => {}
+        }
+    }
+    out.trim().to_string()
+}
+
+/// 按开始时间排序后生成歌词
+fn lyrics_from_lines(meta: LyricsMeta, mut lines: Vec<LyricsLine>) -> Result<Lyrics, LyricsError> {
+    if lines.is_empty() {
+        return Err(LyricsError::EmptyLyrics);
+    }
+    lines.sort_by(|a, b| a.timestamp_start.total_cmp(&b.timestamp_start));
+    Ok(Lyrics {
+        meta,
+        lines,
+        unsynced: false,
+    })
+}
+
+/// 歌词文件格式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LyricsFormat {
+    Lrc,
+    Srt,
+    Vtt,
+    Ttml,
+}
+
+impl LyricsFormat {
+    /// 根据内容识别格式
+    pub fn detect(doc: &str) -> Self {
+        let doc = doc.trim_start_matches('\u{feff}').trim_start();
+        if doc.starts_with("WEBVTT") {
+            Self::Vtt
+        } else if doc.starts_with('<') && doc.contains("<tt") {
+            Self::Ttml
+        } else if Self::is_srt(doc) {
+            Self::Srt
+        } else {
+            Self::Lrc
+        }
+    }
+
+    /// 前 10 行中有 SRT 时间行且没有 LRC 时间标签
+    fn is_srt(doc: &str) -> bool {
+        static LRC_TIME: OnceLock<Regex> = OnceLock::new();
+        static SRT_TIMING: OnceLock<Regex> = OnceLock::new();
+        let lrc_time = LRC_TIME.get_or_init(|| Regex::new(r"^\[\d+:\d+([.:]\d+)?\]").unwrap());
+        let srt_timing =
+            SRT_TIMING.get_or_init(|| Regex::new(r"^\d+:\d\d:\d\d[,.]\d{3}\s*-->").unwrap());
+
+        let lines: Vec<&str> = doc.lines().take(10).map(str::trim).collect();
+        !lines.iter().any(|l| lrc_time.is_match(l)) && lines.iter().any(|l| srt_timing.is_match(l))
+    }
+
+    /// 文件后缀
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Lrc => "lrc",
+            Self::Srt => "srt",
+            Self::Vtt => "vtt",
+            Self::Ttml => "ttml",
+        }
+    }
+}
+
+/// 解析中的逐字片段，结束时间未知时为 None
+type WordTag = (f64, Option<f64>, String);
+
 pub struct LyricParser;
 
 impl LyricParser {
-    pub async fn parse(doc: String, song_duration: f64) -> Result<Vec<LyricsLine>, LyricsError> {
+    /// 按内容识别格式后解析
+    pub async fn parse(doc: String, song_duration: f64) -> Result<Lyrics, LyricsError> {
+        match LyricsFormat::detect(&doc) {
+            LyricsFormat::Lrc => Self::parse_lrc(doc, song_duration).await,
+            LyricsFormat::Srt => srt::parse(&doc),
+            LyricsFormat::Vtt => vtt::parse(&doc),
+            LyricsFormat::Ttml => ttml::parse(&doc),
+        }
+    }
+
+    async fn parse_lrc(doc: String, song_duration: f64) -> Result<Lyrics, LyricsError> {
+        let mut meta = LyricsMeta::default();
         let mut entries = Vec::new();
+        let mut plain = Vec::new();
 
         for line in doc.lines() {
-            if let Ok((time_tags, text)) = Self::parse_line(line).await {
+            if let Some((key, value)) = Self::parse_tag(line) {
+                meta.set_tag(key, value);
+                continue;
+            }
+
+            if let Ok((time_tags, text, words)) = Self::parse_line(line).await {
+                if time_tags.is_empty() {
+                    plain.push(text.clone());
+                }
+
+                // 多时间标签时逐字时间按第一个标签平移
+                let first = time_tags.first().copied().unwrap_or_default();
                 for ts in time_tags {
-                    entries.push((ts, text.clone()));
+                    let words: Vec<WordTag> = words
+                        .iter()
+                        .map(|(start, end, w)| {
+                            (start - first + ts, end.map(|e| e - first + ts), w.clone())
+                        })
+                        .collect();
+                    entries.push((ts, text.clone(), words));
                 }
-            };
+            } else {
+                // 纯文本中的 `[Chorus]` 等
+                plain.push(line.trim().to_string());
+            }
+        }
+
+        // offset 为正时歌词提前；不做截断，保证写回时可还原原始时间
+        if meta.offset != 0 {
+            let shift = |t: f64| t - meta.offset as f64 / 1000.0;
+            for (ts, _, words) in entries.iter_mut() {
+                *ts = shift(*ts);
+                for (start, end, _) in words.iter_mut() {
+                    *start = shift(*start);
+                    *end = end.map(shift);
+                }
+            }
         }
 
         entries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
 
+        // 播放器未提供时长时使用 [length:] 标签
+        let song_duration = match song_duration > 0.0 {
+            true => song_duration,
+            false => meta.length.unwrap_or(song_duration),
+        };
+
+        if entries.is_empty() {
+            return Self::unsynced(meta, plain, song_duration);
+        }
+
         let mut lyrics = Vec::with_capacity(entries.len());
-        for (i, &(start, ref text)) in entries.iter().enumerate() {
+        for (i, (start, text, words)) in entries.iter().enumerate() {
             let end = entries
                 .get(i + 1)
-                .map(|(next_start, _)| *next_start)
+                .map(|(next_start, _, _)| *next_start)
                 .unwrap_or(song_duration);
 
             lyrics.push(LyricsLine {
-                timestamp_start: start,
+                timestamp_start: *start,
                 timestamp_end: end,
                 text: text.clone(),
+                words: Self::close_words(words, end),
+                translation: None,
+                romanization: None,
             });
         }
 
         if lyrics.is_empty() {
             Err(LyricsError::EmptyLyrics)
         } else {
-            Ok(lyrics)
+            Ok(Lyrics {
+                meta,
+                lines: lyrics,
+                unsynced: false,
+            })
+        }
+    }
+
+    /// 无时间标签时按纯文本处理，去掉首尾空行
+    fn unsynced(
+        meta: LyricsMeta,
+        plain: Vec<String>,
+        song_duration: f64,
+    ) -> Result<Lyrics, LyricsError> {
+        let start = plain.iter().position(|l| !l.is_empty());
+        let end = plain.iter().rposition(|l| !l.is_empty());
+        let (Some(start), Some(end)) = (start, end) else {
+            return Err(LyricsError::EmptyLyrics);
+        };
+
+        let lines = plain[start..=end]
+            .iter()
+            .map(|text| LyricsLine {
+                timestamp_start: 0.0,
+                timestamp_end: song_duration,
+                text: text.clone(),
+                words: Vec::new(),
+                translation: None,
+                romanization: None,
+            })
+            .collect();
+
+        Ok(Lyrics {
+            meta,
+            lines,
+            unsynced: true,
+        })
+    }
+
+    /// 解析原文并合并翻译和罗马音，解析失败时忽略
+    pub async fn parse_doc(doc: LyricsDoc, song_duration: f64) -> Result<Lyrics, LyricsError> {
+        let mut lyrics = Self::parse(doc.lyric, song_duration).await?;
+        if let Some(translation) = doc.translation
+            && let Ok(translation) = Self::parse(translation, song_duration).await
+        {
+            lyrics.merge_translation(&translation);
+        }
+        if let Some(romanization) = doc.romanization
+            && let Ok(romanization) = Self::parse(romanization, song_duration).await
+        {
+            lyrics.merge_romanization(&romanization);
         }
+        Ok(lyrics)
     }
 
-    async fn parse_line(line: &str) -> Result<(Vec<f64>, String), LyricsError> {
+    /// 按原始顺序列出 LRC 每行的第一个时间标签与文本
+    pub async fn line_starts(doc: &str) -> Vec<(f64, String)> {
+        let mut starts = Vec::new();
+        for line in doc.lines() {
+            if let Ok((time_tags, text, _)) = Self::parse_line(line).await
+                && let Some(start) = time_tags.first()
+            {
+                starts.push((*start, text));
+            }
+        }
+        starts
+    }
+
+    /// 识别 `[key:value]` 形式的头部标签
+    fn parse_tag(line: &str) -> Option<(&str, &str)> {
+        let inner = line.trim().strip_prefix('[')?.strip_suffix(']')?;
+        let (key, value) = inner.split_once(':')?;
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphabetic()) {
+            return None;
+        }
+        Some((key, value))
+    }
+
+    async fn parse_line(line: &str) -> Result<(Vec<f64>, String, Vec<WordTag>), LyricsError> {
         let mut line = line.trim();
         let mut time_tags = Vec::new();
 
@@ -94,24 +649,202 @@ impl LyricParser {
             }
         }
 
-        let text = line.trim().to_string();
+        let (text, words) = Self::parse_words(line.trim(), time_tags.first().copied()).await;
 
-        Ok((time_tags, text))
+        Ok((time_tags, text, words))
     }
 
-    async fn parse_time(s: &str) -> Option<f64> {
-        let parts: Vec<&str> = s.split(&[':', '.']).collect();
-        if parts.len() < 2 {
-            return None;
+    /// 解析 `<mm:ss.xx>` 逐字标签，返回去除标签后的文本
+    async fn parse_words(line: &str, line_start: Option<f64>) -> (String, Vec<WordTag>) {
+        if !line.contains('<') {
+            return (line.to_string(), Vec::new());
         }
 
-        let minutes = parts[0].parse::<f64>().ok()?;
-        let seconds = parts[1].parse::<f64>().ok()?;
-        let millis = parts
-            .get(2)
-            .and_then(|s| s.parse::<f64>().ok())
-            .unwrap_or(0.0);
+        let mut words: Vec<WordTag> = Vec::new();
+        let mut text = String::new();
+        let mut rest = line;
+        let mut timed = false;
+
+        while !rest.is_empty() {
+            let tag = rest
+                .strip_prefix('<')
+                .and_then(|r| r.find('>').map(|idx| &r[..idx]));
+            if let Some(tag) = tag
+                && let Some(time) = Self::parse_time(tag).await
+            {
+                if let Some(last) = words.last_mut()
+                    && last.1.is_none()
+                {
+                    last.1 = Some(time);
+                }
+                words.push((time, None, String::new()));
+                timed = true;
+                rest = &rest[tag.len() + 2..];
+                continue;
+            }
+
+            // 读取到下一个标签之前的文字
+            let next = rest
+                .char_indices()
+                .skip(1)
+                .find(|(_, c)| *c == '<')
+                .map(|(i, _)| i)
+                .unwrap_or(rest.len());
+            let segment = &rest[..next];
+            match words.last_mut() {
+                Some(last) => last.2.push_str(segment),
+                None => words.push((line_start.unwrap_or_default(), None, segment.to_string())),
+            }
+            text.push_str(segment);
+            rest = &rest[next..];
+        }
+
+        // `I <3 you` 等没有时间标签的行按普通 LRC 处理
+        if !timed {
+            return (line.to_string(), Vec::new());
+        }
+
+        // 结尾的空白标签只用于标记上一个字的结束时间
+        words.retain(|(_, _, w)| !w.is_empty());
+        if let Some(first) = words.first_mut() {
+            first.2 = first.2.trim_start().to_string();
+        }
+        if let Some(last) = words.last_mut() {
+            last.2 = last.2.trim_end().to_string();
+        }
+
+        (text.trim().to_string(), words)
+    }
+
+    fn close_words(words: &[WordTag], line_end: f64) -> Vec<LyricsWord> {
+        words
+            .iter()
+            .map(|(start, end, text)| LyricsWord {
+                timestamp_start: *start,
+                timestamp_end: end.unwrap_or(line_end).max(*start),
+                text: text.clone(),
+            })
+            .collect()
+    }
+
+    async fn parse_time(s: &str) -> Option<f64> {
+        let (minutes, rest) = s.split_once(':')?;
+        let (seconds, fraction) = match rest.split_once(['.', ':']) {
+            Some((sec, frac)) => (sec, frac),
+            None => (rest, ""),
+        };
+
+        let minutes = minutes.trim().parse::<u64>().ok()?;
+        let seconds = seconds.trim().parse::<u64>().ok()?;
+        // 小数部分按位数计算: .5 / .50 / .500
+        let millis = match fraction.len() {
+            0 => 0,
+            1 => fraction.parse::<u64>().ok()? * 100,
+            2 => fraction.parse::<u64>().ok()? * 10,
+            _ => fraction.get(..3)?.parse::<u64>().ok()?,
+        };
+
+        Some(((minutes * 60 + seconds) * 1000 + millis) as f64 / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn angle_bracket_without_time_tag_is_plain_text() {
+        let lyrics = LyricParser::parse("[00:01.00]I <3 you".to_string(), 10.0)
+            .await
+            .unwrap();
+        assert_eq!(lyrics.lines[0].text, "I <3 you");
+        assert!(lyrics.lines[0].words.is_empty());
+    }
+
+    #[tokio::test]
+    async fn word_tags_are_parsed() {
+        let lyrics = LyricParser::parse(
+            "[00:01.00]<00:01.00>I <00:01.50>love<00:02.00>".to_string(),
+            10.0,
+        )
+        .await
+        .unwrap();
+        let words = &lyrics.lines[0].words;
+        assert_eq!(lyrics.lines[0].text, "I love");
+        assert_eq!(words.len(), 2);
+        assert_eq!(words[1].text, "love");
+        assert_eq!(words[1].timestamp_start, 1.5);
+        assert_eq!(words[1].timestamp_end, 2.0);
+    }
+
+    #[test]
+    fn detect_srt_only_from_timing_lines() {
+        let srt = "1\n00:00:01,000 --> 00:00:02,000\nhello\n";
+        assert_eq!(LyricsFormat::detect(srt), LyricsFormat::Srt);
+        let lrc = "[ti:t]\n[00:01.00]go --> there, now\n[00:02.00]next";
+        assert_eq!(LyricsFormat::detect(lrc), LyricsFormat::Lrc);
+        assert_eq!(LyricsFormat::detect("go --> there, now"), LyricsFormat::Lrc);
+    }
+
+    /// 解析、写回后再解析，结果应一致
+    async fn round_trip(doc: &str) -> (Lyrics, String) {
+        let lyrics = LyricParser::parse(doc.to_string(), 30.0).await.unwrap();
+        let written = LrcWriter::write(&lyrics);
+        let reparsed = LyricParser::parse(written.clone(), 30.0).await.unwrap();
+        assert_eq!(reparsed, lyrics, "written:\n{written}");
+        (lyrics, written)
+    }
+
+    #[tokio::test]
+    async fn round_trip_header_tags() {
+        let (lyrics, written) = round_trip(
+            "[ti:Title]\n[ar:Artist]\n[al:Album]\n[by:me]\n[length:03:20.50]\n[00:01.00]a\n[00:02.00]b",
+        )
+        .await;
+        assert_eq!(lyrics.meta.title.as_deref(), Some("Title"));
+        assert_eq!(lyrics.meta.length, Some(200.5));
+        assert!(
+            written
+                .starts_with("[ti:Title]\n[ar:Artist]\n[al:Album]\n[by:me]\n[length:03:20.50]\n")
+        );
+    }
+
+    #[tokio::test]
+    async fn round_trip_word_timings() {
+        let (lyrics, written) = round_trip(
+            "[00:01.00]<00:01.00>I <00:01.50>love<00:02.00><00:02.500>you\n[00:04.00]end",
+        )
+        .await;
+        assert_eq!(lyrics.lines[0].words.len(), 3);
+        assert_eq!(lyrics.lines[0].words[2].timestamp_start, 2.5);
+        assert!(written.contains("<00:02.00><00:02.50>you<00:04.00>"));
+    }
+
+    #[tokio::test]
+    async fn round_trip_compacts_repeated_lines() {
+        let (lyrics, written) =
+            round_trip("[00:01.00][00:05.00]chorus\n[00:03.00]verse\n[00:07.00]chorus").await;
+        assert_eq!(lyrics.lines.len(), 4);
+        assert_eq!(
+            written,
+            "[00:01.00][00:05.00][00:07.00]chorus\n[00:03.00]verse\n"
+        );
+    }
+
+    #[tokio::test]
+    async fn round_trip_keeps_offset() {
+        let (lyrics, written) = round_trip("[offset:500]\n[00:00.20]a\n[00:02.00]b").await;
+        assert_eq!(lyrics.meta.offset, 500);
+        assert!((lyrics.lines[0].timestamp_start + 0.3).abs() < 1e-9);
+        assert_eq!(written, "[offset:500]\n[00:00.20]a\n[00:02.00]b\n");
+    }
 
-        Some(minutes * 60.0 + seconds + millis / 100.0)
+    #[tokio::test]
+    async fn malformed_fraction_does_not_panic() {
+        assert_eq!(LyricParser::parse_time("1:2.a中").await, None);
+        let lyrics = LyricParser::parse("[00:01.00]<1:2.a中>text".to_string(), 10.0)
+            .await
+            .unwrap();
+        assert!(lyrics.lines[0].words.is_empty());
     }
 }
@@ -0,0 +1,68 @@
+//! SubRip (.srt)
+//!
+//! 每个字幕块的第一行为原文，其余行作为翻译。
+
+use super::{
+    Lyrics, LyricsLine, LyricsMeta, cue_blocks, format_clock, lyrics_from_lines, parse_cue_timing,
+    strip_tags,
+};
+use crate::error::LyricsError;
+
+pub(super) fn parse(doc: &str) -> Result<Lyrics, LyricsError> {
+    let mut lines = Vec::new();
+
+    for block in cue_blocks(doc) {
+        // 序号行可省略
+        let Some(timing_idx) = block.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end)) = parse_cue_timing(block[timing_idx]) else {
+            continue;
+        };
+
+        let mut rows = block[timing_idx + 1..].iter().map(|l| strip_tags(l));
+        let text = rows.next().unwrap_or_default();
+        let translation: Vec<String> = rows.filter(|l| !l.is_empty()).collect();
+
+        lines.push(LyricsLine {
+            timestamp_start: start,
+            timestamp_end: end,
+            text,
+            words: Vec::new(),
+            translation: (!translation.is_empty()).then(|| translation.join(" ")),
+            romanization: None,
+        });
+    }
+
+    lyrics_from_lines(LyricsMeta::default(), lines)
+}
+
+/// 输出 SRT，翻译写在原文下一行
+pub struct SrtWriter;
+
+impl SrtWriter {
+    pub fn write(lyrics: &Lyrics) -> String {
+        let cues: Vec<String> = lyrics
+            .lines
+            .iter()
+            .filter(|line| !line.text.is_empty())
+            .enumerate()
+            .map(|(i, line)| {
+                let mut cue = format!(
+                    "{}\n{} --> {}\n{}",
+                    i + 1,
+                    format_clock(line.timestamp_start, ','),
+                    format_clock(line.timestamp_end.max(line.timestamp_start), ','),
+                    line.text
+                );
+                if let Some(translation) = &line.translation {
+                    cue.push('\n');
+                    cue.push_str(translation);
+                }
+                cue
+            })
+            .collect();
+
+        cues.join("\n\n") + "\n"
+    }
+}
@@ -0,0 +1,202 @@
+//! Apple 风格 TTML (.ttml)
+//!
+//! `<p begin end>` 为行，带 `begin` 的 `<span>` 为逐字时间，
+//! `ttm:role="x-translation"` / `x-roman` 分别为翻译与音译。
+
+use roxmltree::{Document, Node};
+
+use super::{
+    Lyrics, LyricsLine, LyricsMeta, LyricsWord, format_clock, lyrics_from_lines, parse_clock,
+};
+use crate::error::LyricsError;
+
+const TTML_NS: &str = "http://www.w3.org/ns/ttml";
+const TTM_NS: &str = "http://www.w3.org/ns/ttml#metadata";
+
+pub(super) fn parse(doc: &str) -> Result<Lyrics, LyricsError> {
+    let doc = Document::parse(doc.trim_start_matches('\u{feff}'))
+        .map_err(|_| LyricsError::LyricsDecodeError)?;
+
+    let mut meta = LyricsMeta::default();
+    if let Some(title) = doc
+        .descendants()
+        .find(|n| n.has_tag_name("title"))
+        .and_then(|n| n.text())
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+    {
+        meta.title = Some(title.to_string());
+    }
+
+    let lines = doc
+        .descendants()
+        .filter(|n| n.has_tag_name("p"))
+        .filter_map(parse_paragraph)
+        .collect();
+
+    lyrics_from_lines(meta, lines)
+}
+
+fn parse_paragraph(p: Node) -> Option<LyricsLine> {
+    let start = p.attribute("begin").and_then(parse_time)?;
+    let end = p
+        .attribute("end")
+        .and_then(parse_time)
+        .or_else(|| p.attribute("dur").and_then(parse_time).map(|d| start + d))
+        .unwrap_or(start);
+
+    let mut line = LyricsLine {
+        timestamp_start: start,
+        timestamp_end: end,
+        text: String::new(),
+        words: Vec::new(),
+        translation: None,
+        romanization: None,
+    };
+    collect(p, &mut line);
+
+    line.text = collapse_whitespace(&line.text);
+    if let Some(last) = line.words.last_mut() {
+        last.text = last.text.trim_end().to_string();
+    }
+    Some(line)
+}
+
+/// 递归收集文本与逐字时间，`x-bg` 等无时间容器展开处理
+fn collect(node: Node, line: &mut LyricsLine) {
+    for child in node.children() {
+        if child.is_text() {
+            let text = child.text().unwrap_or_default();
+            // 词间空白并入前一个词
+            if let Some(word) = line.words.last_mut()
+                && !text.is_empty()
+                && text.trim().is_empty()
+                && !word.text.ends_with(' ')
+            {
+                word.text.push(' ');
+            }
+            line.text.push_str(text);
+            continue;
+        }
+        if !child.is_element() {
+            continue;
+        }
+
+        match child.attribute((TTM_NS, "role")) {
+            Some("x-translation") => line.translation = text_of(child),
+            Some("x-roman") => line.romanization = text_of(child),
+            _ => match child.attribute("begin").and_then(parse_time) {
+                Some(start) => {
+                    let text = child
+                        .descendants()
+                        .filter(|n| n.is_text())
+                        .filter_map(|n| n.text())
+                        .collect::<String>();
+                    line.words.push(LyricsWord {
+                        timestamp_start: start,
+                        timestamp_end: child.attribute("end").and_then(parse_time).unwrap_or(start),
+                        text: text.clone(),
+                    });
+                    line.text.push_str(&text);
+                }
+                None => collect(child, line),
+            },
+        }
+    }
+}
+
+fn text_of(node: Node) -> Option<String> {
+    let text = node
+        .descendants()
+        .filter(|n| n.is_text())
+        .filter_map(|n| n.text())
+        .collect::<String>();
+    let text = collapse_whitespace(&text);
+    (!text.is_empty()).then_some(text)
+}
+
+fn collapse_whitespace(s: &str) -> String {
+    s.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// 时钟时间 `hh:mm:ss.mmm`，或偏移时间 `1.5s` / `1500ms` / `1m`
+fn parse_time(s: &str) -> Option<f64> {
+    let s = s.trim();
+    if let Some(ms) = s.strip_suffix("ms") {
+        return ms.parse::<f64>().ok().map(|v| v / 1000.0);
+    }
+    for (unit, scale) in [('h', 3600.0), ('m', 60.0), ('s', 1.0)] {
+        if let Some(v) = s.strip_suffix(unit) {
+            return v.parse::<f64>().ok().map(|v| v * scale);
+        }
+    }
+    parse_clock(s)
+}
+
+/// 输出 TTML，逐字时间写为 `<span>`，翻译与音译写为带 `ttm:role` 的 `<span>`
+pub struct TtmlWriter;
+
+impl TtmlWriter {
+    pub fn write(lyrics: &Lyrics) -> String {
+        let mut out = format!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tt xmlns=\"{TTML_NS}\" xmlns:ttm=\"{TTM_NS}\">\n"
+        );
+
+        if let Some(title) = &lyrics.meta.title {
+            out.push_str(&format!(
+                "  <head>\n    <metadata>\n      <ttm:title>{}</ttm:title>\n    </metadata>\n  </head>\n",
+                escape(title)
+            ));
+        }
+
+        out.push_str("  <body>\n    <div>\n");
+        for line in lyrics.lines.iter().filter(|line| !line.text.is_empty()) {
+            out.push_str(&format!(
+                "      <p begin=\"{}\" end=\"{}\">",
+                format_clock(line.timestamp_start, '.'),
+                format_clock(line.timestamp_end.max(line.timestamp_start), '.')
+            ));
+
+            match line.words.is_empty() {
+                true => out.push_str(&escape(&line.text)),
+                false => {
+                    for word in &line.words {
+                        let text = word.text.trim_end();
+                        out.push_str(&format!(
+                            "<span begin=\"{}\" end=\"{}\">{}</span>",
+                            format_clock(word.timestamp_start, '.'),
+                            format_clock(word.timestamp_end.max(word.timestamp_start), '.'),
+                            escape(text)
+                        ));
+                        if text.len() < word.text.len() {
+                            out.push(' ');
+                        }
+                    }
+                }
+            }
+
+            for (role, text) in [
+                ("x-translation", &line.translation),
+                ("x-roman", &line.romanization),
+            ] {
+                if let Some(text) = text {
+                    out.push_str(&format!(
+                        "<span ttm:role=\"{role}\">{}</span>",
+                        escape(text)
+                    ));
+                }
+            }
+            out.push_str("</p>\n");
+        }
+        out.push_str("    </div>\n  </body>\n</tt>\n");
+
+        out
+    }
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
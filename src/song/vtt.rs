@@ -0,0 +1,133 @@
+//! WebVTT (.vtt)
+//!
+//! 字幕中的 `<hh:mm:ss.mmm>` 时间标签作为逐字时间，
+//! 第一行为原文，其余行作为翻译。
+
+use super::{
+    Lyrics, LyricsLine, LyricsMeta, LyricsWord, cue_blocks, format_clock, lyrics_from_lines,
+    parse_clock, parse_cue_timing, strip_tags,
+};
+use crate::error::LyricsError;
+
+pub(super) fn parse(doc: &str) -> Result<Lyrics, LyricsError> {
+    let mut lines = Vec::new();
+
+    for block in cue_blocks(doc) {
+        // 跳过 WEBVTT 头部与 NOTE/STYLE/REGION 块
+        let Some(timing_idx) = block.iter().position(|l| l.contains("-->")) else {
+            continue;
+        };
+        let Some((start, end)) = parse_cue_timing(block[timing_idx]) else {
+            continue;
+        };
+
+        let mut rows = block[timing_idx + 1..].iter();
+        let (text, words) = rows
+            .next()
+            .map(|l| parse_cue_text(l, start, end))
+            .unwrap_or_default();
+        let translation: Vec<String> = rows
+            .map(|l| strip_tags(l))
+            .filter(|l| !l.is_empty())
+            .collect();
+
+        lines.push(LyricsLine {
+            timestamp_start: start,
+            timestamp_end: end,
+            text,
+            words,
+            translation: (!translation.is_empty()).then(|| translation.join(" ")),
+            romanization: None,
+        });
+    }
+
+    lyrics_from_lines(LyricsMeta::default(), lines)
+}
+
+/// 解析字幕文本中的时间标签，其他标签（`<c>` `<v>` `<b>` 等）丢弃
+fn parse_cue_text(text: &str, start: f64, end: f64) -> (String, Vec<LyricsWord>) {
+    let mut segments = vec![(start, String::new())];
+    let mut timed = false;
+    let mut rest = text;
+
+    while let Some(open) = rest.find('<') {
+        segments.last_mut().unwrap().1.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = &rest[open + 1..open + close];
+        if tag.contains(':')
+            && let Some(time) = parse_clock(tag)
+        {
+            segments.push((time, String::new()));
+            timed = true;
+        }
+        rest = &rest[open + close + 1..];
+    }
+    segments.last_mut().unwrap().1.push_str(rest);
+
+    let line_text = segments
+        .iter()
+        .map(|(_, t)| t.as_str())
+        .collect::<String>()
+        .trim()
+        .to_string();
+    if !timed {
+        return (line_text, Vec::new());
+    }
+
+    let segments: Vec<(f64, String)> = segments
+        .into_iter()
+        .filter(|(_, t)| !t.is_empty())
+        .collect();
+    let words = segments
+        .iter()
+        .enumerate()
+        .map(|(i, (word_start, word))| LyricsWord {
+            timestamp_start: *word_start,
+            timestamp_end: segments.get(i + 1).map_or(end, |(next, _)| *next),
+            text: word.clone(),
+        })
+        .collect();
+
+    (line_text, words)
+}
+
+/// 输出 WebVTT，逐字时间写为时间标签，翻译写在原文下一行
+pub struct VttWriter;
+
+impl VttWriter {
+    pub fn write(lyrics: &Lyrics) -> String {
+        let mut cues = vec!["WEBVTT".to_string()];
+
+        for line in lyrics.lines.iter().filter(|line| !line.text.is_empty()) {
+            let text = match line.words.is_empty() {
+                true => line.text.clone(),
+                false => line
+                    .words
+                    .iter()
+                    .enumerate()
+                    .map(|(i, word)| match i {
+                        0 => word.text.clone(),
+                        _ => format!("<{}>{}", format_clock(word.timestamp_start, '.'), word.text),
+                    })
+                    .collect(),
+            };
+
+            let mut cue = format!(
+                "{} --> {}\n{}",
+                format_clock(line.timestamp_start, '.'),
+                format_clock(line.timestamp_end.max(line.timestamp_start), '.'),
+                text
+            );
+            if let Some(translation) = &line.translation {
+                cue.push('\n');
+                cue.push_str(translation);
+            }
+            cues.push(cue);
+        }
+
+        cues.join("\n\n") + "\n"
+    }
+}
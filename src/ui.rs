@@ -4,6 +4,7 @@ use anyhow::Result;
 use crossterm::event::{Event, EventStream, KeyCode, KeyEventKind};
 use help::HelpScreen;
 use lyrics::LyricsScreen;
+use players::PlayersScreen;
 use ratatui::{
     Frame,
     buffer::Buffer,
@@ -19,6 +20,7 @@ use tokio_stream::StreamExt;
 
 mod help;
 mod lyrics;
+mod players;
 mod search;
 
 #[derive(Default, Clone, Debug)]
@@ -26,6 +28,7 @@ enum Screen {
     #[default]
     Lyrics,
     Search,
+    Players,
     Help,
 }
 
@@ -36,6 +39,7 @@ pub struct App {
 
     lyrics: LyricsScreen,
     search: SearchScreen,
+    players: PlayersScreen,
     help: HelpScreen,
 }
 
@@ -60,6 +64,7 @@ impl App {
                 Some(Ok(event)) = events.next() => self.handle_event(&event).await,
             }
         }
+        self.lyrics.save_offset().await;
         Ok(())
     }
 
@@ -81,6 +86,13 @@ impl App {
                 }
                 self.search.update().await;
             }
+            Screen::Players => {
+                if self.players.player_changed() {
+                    self.lyrics.save_offset().await;
+                    self.lyrics.reset();
+                }
+                self.players.update().await;
+            }
             _ => {}
         }
     }
@@ -91,6 +103,7 @@ impl App {
         match self.screen {
             Screen::Lyrics => self.lyrics.render(area, buf),
             Screen::Search => self.search.render(area, buf),
+            Screen::Players => self.players.render(area, buf),
             Screen::Help => self.help.render(area, buf),
         }
     }
@@ -103,6 +116,7 @@ impl App {
                 Screen::Lyrics => match key.code {
                     KeyCode::Char('h') | KeyCode::Char('?') => self.screen = Screen::Help,
                     KeyCode::Char('s') => self.screen = Screen::Search,
+                    KeyCode::Char('P') => self.screen = Screen::Players,
                     KeyCode::Char('q') | KeyCode::Esc => self.exit(),
                     _ => self.lyrics.handle_key_event(key).await,
                 },
@@ -111,6 +125,11 @@ impl App {
                     KeyCode::Char('h') | KeyCode::Char('?') => self.screen = Screen::Help,
                     _ => self.search.handle_key_event(key).await,
                 },
+                Screen::Players => match key.code {
+                    KeyCode::Char('q') | KeyCode::Esc => self.screen = Screen::Lyrics,
+                    KeyCode::Char('h') | KeyCode::Char('?') => self.screen = Screen::Help,
+                    _ => self.players.handle_key_event(key).await,
+                },
                 Screen::Help => match key.code {
                     KeyCode::Char('q') | KeyCode::Esc => self.screen = Screen::Lyrics,
                     KeyCode::Char('t') => self.toggle_locale(),
@@ -8,7 +8,7 @@ use ratatui::{
 };
 use rust_i18n::t;
 
-use crate::ui::SearchScreen;
+use crate::ui::{PlayersScreen, SearchScreen};
 
 use super::{HELP_KEY_STYLE, LyricsScreen};
 
@@ -21,6 +21,8 @@ impl HelpScreen {
         let lyric_lines = LyricsScreen::help();
         // search
         let search_lines = SearchScreen::help();
+        // players
+        let players_lines = PlayersScreen::help();
         // help
         let help_lines = vec![("q | ESC ", t!("help.help.quit"))];
 
@@ -29,13 +31,15 @@ impl HelpScreen {
             [
                 Constraint::Min(lyric_lines.len() as u16 + 2),
                 Constraint::Min(search_lines.len() as u16 + 2),
+                Constraint::Min(players_lines.len() as u16 + 2),
                 Constraint::Min(help_lines.len() as u16 + 2),
             ],
         );
-        let [lyric_chunk, search_chunk, help_chunk] = chunks.areas(area);
+        let [lyric_chunk, search_chunk, players_chunk, help_chunk] = chunks.areas(area);
 
         help(lyric_lines).render(lyric_chunk, buf);
         help(search_lines).render(search_chunk, buf);
+        help(players_lines).render(players_chunk, buf);
         help(help_lines).render(help_chunk, buf);
     }
 }
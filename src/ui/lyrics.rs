@@ -1,12 +1,15 @@
-use std::borrow::Cow;
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
 
 use crate::{
     client::get_lyrics_client,
-    config::get_config,
+    config::{RomanizationMode, TranslationMode, get_config},
     error::LyricsError,
     song::{
-        LyricParser, LyricsLine, PlayTime, PlayerAction, SongInfo, get_current_song,
-        get_current_time_song, player_action,
+        LyricParser, Lyrics, LyricsLine, PlayTime, PlayerAction, SongInfo, TrackId,
+        get_current_song, get_current_time_song, player_action,
     },
 };
 use crossterm::event::{KeyCode, KeyEvent};
@@ -18,7 +21,7 @@ use ratatui::{
     widgets::{Block, Borders, Gauge, Padding, Paragraph, Widget, Wrap},
 };
 use rust_i18n::t;
-use tracing::{info, warn};
+use tracing::info;
 
 use super::{LYRICS_GAUGE_STYLE, LYRICS_HEADER_STYLE, render_error};
 
@@ -59,14 +62,27 @@ impl LyricsScreen {
     }
 
     fn get_window_title(&self) -> String {
-        match !self.state.song.title.is_empty() {
+        let title = match !self.state.song.title.is_empty() {
             true => self.state.song.title.clone(),
             false => " No song playing ".into(),
+        };
+        let lyrics = &self.state.lyrics;
+        if lyrics.unsynced {
+            format!("{} [{}]", title, t!("lyrics.unsynced"))
+        } else if lyrics.meta.offset != 0 {
+            format!(
+                "{} [{}: {:+}ms]",
+                title,
+                t!("lyrics.offset"),
+                lyrics.meta.offset
+            )
+        } else {
+            title
         }
     }
 
     pub fn render_title(&self, area: Rect, buf: &mut Buffer) {
-        if self.state.song.title.is_empty() {
+        if self.state.song.title.is_empty() && self.state.lyrics.meta.title.is_none() {
             return;
         }
         // 渲染标题区块
@@ -74,11 +90,18 @@ impl LyricsScreen {
             .borders(Borders::ALL)
             .style(LYRICS_HEADER_STYLE);
 
-        // 显示歌曲信息
+        // 显示歌曲信息，播放器缺失的字段使用歌词标签补充
         let song = &self.state.song.clone();
+        let meta = &self.state.lyrics.meta;
 
-        let line_title = song.title.clone();
-        let line_artist = song.artist.clone();
+        let line_title = match (song.title.is_empty(), &meta.title) {
+            (true, Some(title)) => title.clone(),
+            _ => song.title.clone(),
+        };
+        let line_artist = match (song.artist.is_empty(), &meta.artist) {
+            (true, Some(artist)) => artist.clone(),
+            _ => song.artist.clone(),
+        };
 
         let lines = vec![Line::raw(line_title), Line::raw(line_artist)];
 
@@ -126,26 +149,54 @@ impl LyricsScreen {
             return;
         }
 
+        let block = Block::default()
+            .title(self.get_window_title())
+            .borders(Borders::ALL)
+            .padding(Padding::horizontal(1));
+
+        if state.instrumental {
+            let height = area.height.saturating_sub(2) as usize;
+            let mut lines = vec![Line::raw(""); height / 2];
+            lines.push(Line::styled(
+                format!("♪ {} ♪", t!("lyrics.instrumental")),
+                Style::new()
+                    .fg(Color::Indexed(245))
+                    .add_modifier(Modifier::ITALIC),
+            ));
+            Paragraph::new(lines)
+                .block(block)
+                .centered()
+                .render(area, buf);
+            return;
+        }
+
         // 使用预计算的显示参数
         // TODO: 当一行的内容超出宽度换行时候计算偏移高度
         let metrics = &state.view_metrics;
         let start = state.target_scroll.min(metrics.scroll_range);
         let end = (start + metrics.visible_lines).min(metrics.content_height);
+        // 无时间轴时不高亮
+        let current = match state.lyrics.unsynced {
+            true => None,
+            false => Some(state.find_current_line().unwrap_or(0)),
+        };
+        let translation = match state.lyrics.has_translation() {
+            true => config.translation,
+            false => TranslationMode::Hidden,
+        };
+        let romanization = match state.lyrics.has_romanization() {
+            true => config.romanization,
+            false => RomanizationMode::Original,
+        };
+
         let mut lines = Vec::new();
-        for (i, line) in state.lyrics[start..end].iter().enumerate() {
-            let is_current = start + i == state.find_current_line().unwrap_or(0);
-
-            let line_text = match config.time {
-                true => format!(
-                    "[{:0>2}:{:0>2}] {}",
-                    (line.timestamp_start / 60.0).floor() as u64,
-                    (line.timestamp_start % 60.0).floor() as u64,
-                    line.text
-                ),
-                false => line.text.clone(),
-            };
+        let mut translations = Vec::new();
+        for (i, line) in state.lyrics.lines[start..end].iter().enumerate() {
+            let is_current = Some(start + i) == current;
 
-            let style = if is_current {
+            let style = if state.lyrics.unsynced {
+                Style::new().fg(Color::Reset).bg(Color::Reset)
+            } else if is_current {
                 Style::new()
                     .fg(Color::Indexed(196))
                     .bg(Color::Reset)
@@ -156,22 +207,76 @@ impl LyricsScreen {
                     .bg(Color::Reset)
             };
 
-            let line = Line::styled(line_text, style);
-            match config.text_center {
-                true => lines.push(line.centered()),
-                false => lines.push(line),
+            let mut spans = Vec::new();
+            if config.time {
+                // offset 可能使开头的行早于 0，显示时截断
+                let start = line.timestamp_start.max(0.0);
+                spans.push(Span::raw(format!(
+                    "[{:0>2}:{:0>2}] ",
+                    (start / 60.0).floor() as u64,
+                    (start % 60.0).floor() as u64,
+                )));
+            }
+
+            match (romanization, &line.romanization) {
+                (RomanizationMode::Romanization, Some(roma)) => spans.push(Span::raw(roma.clone())),
+                _ if is_current && !line.words.is_empty() => {
+                    spans.extend(karaoke_spans(line, state.play_time.current_time))
+                }
+                _ => spans.push(Span::raw(line.text.clone())),
+            }
+
+            let align = |line: Line<'static>| match config.text_center {
+                true => line.centered(),
+                false => line,
+            };
+
+            // 罗马音以注音形式显示在原文上方
+            if romanization == RomanizationMode::Both {
+                lines.push(align(Line::styled(
+                    line.romanization.clone().unwrap_or_default(),
+                    Style::new()
+                        .fg(Color::Indexed(245))
+                        .bg(Color::Reset)
+                        .add_modifier(Modifier::DIM),
+                )));
+                if translation == TranslationMode::SideBySide {
+                    translations.push(Line::raw(""));
+                }
+            }
+
+            lines.push(align(Line::from(spans).style(style)));
+
+            let translation_line = align(Line::styled(
+                line.translation.clone().unwrap_or_default(),
+                translation_style(is_current),
+            ));
+            match translation {
+                TranslationMode::Below => lines.push(translation_line),
+                TranslationMode::SideBySide => translations.push(translation_line),
+                TranslationMode::Hidden => {}
             }
         }
 
-        let block = Block::default()
-            .title(self.get_window_title())
-            .borders(Borders::ALL)
-            .padding(Padding::horizontal(1));
+        if translation != TranslationMode::SideBySide {
+            Paragraph::new(lines)
+                .block(block)
+                .wrap(Wrap { trim: true })
+                .render(area, buf);
+            return;
+        }
 
-        Paragraph::new(lines)
-            .block(block)
-            .wrap(Wrap { trim: true })
-            .render(area, buf);
+        // 原文与翻译左右并列，不换行以保持对齐
+        let inner = block.inner(area);
+        block.render(area, buf);
+        let [left, right] = Layout::new(
+            Direction::Horizontal,
+            [Constraint::Percentage(50), Constraint::Percentage(50)],
+        )
+        .spacing(1)
+        .areas(inner);
+        Paragraph::new(lines).render(left, buf);
+        Paragraph::new(translations).render(right, buf);
     }
 
     pub async fn handle_key_event(&mut self, key_event: &KeyEvent) {
@@ -189,6 +294,27 @@ impl LyricsScreen {
                 let config = &mut get_config().write().unwrap();
                 config.ui.text_center = !config.ui.text_center;
             }
+            KeyCode::Up => self.state.scroll_by(-1),
+            KeyCode::Down => self.state.scroll_by(1),
+            KeyCode::PageUp => self
+                .state
+                .scroll_by(-(self.state.view_metrics.visible_lines as isize)),
+            KeyCode::PageDown => self
+                .state
+                .scroll_by(self.state.view_metrics.visible_lines as isize),
+            KeyCode::Char('a') => self.state.auto_scroll = !self.state.auto_scroll,
+            KeyCode::Char(']') => self.state.adjust_offset(100),
+            KeyCode::Char('[') => self.state.adjust_offset(-100),
+            KeyCode::Char('}') => self.state.adjust_offset(500),
+            KeyCode::Char('{') => self.state.adjust_offset(-500),
+            KeyCode::Char('r') => {
+                let config = &mut get_config().write().unwrap();
+                config.ui.romanization = config.ui.romanization.next();
+            }
+            KeyCode::Char('T') => {
+                let config = &mut get_config().write().unwrap();
+                config.ui.translation = config.ui.translation.next();
+            }
             KeyCode::Char('t') => {
                 let config = &mut get_config().write().unwrap();
                 config.ui.title = !config.ui.title;
@@ -208,7 +334,14 @@ impl LyricsScreen {
             ("n | j ", t!("help.lyrics.prev_song")),
             ("p | k ", t!("help.lyrics.next_song")),
             ("t", t!("help.lyrics.toggle_title")),
+            ("T", t!("help.lyrics.toggle_translation")),
+            ("r", t!("help.lyrics.toggle_romanization")),
+            ("Up | Down ", t!("help.lyrics.scroll")),
+            ("a", t!("help.lyrics.auto_scroll")),
+            ("[ | ] ", t!("help.lyrics.offset_small")),
+            ("{ | } ", t!("help.lyrics.offset_large")),
             ("s", t!("help.lyrics.search")),
+            ("P", t!("help.lyrics.players")),
         ]
     }
 
@@ -227,11 +360,54 @@ impl LyricsScreen {
         self.state.delete().await;
     }
 
+    /// 保存未写入的偏移
+    pub async fn save_offset(&mut self) {
+        self.state.save_offset(true).await;
+    }
+
     pub fn reset(&mut self) {
         self.state.reset();
     }
 }
 
+fn translation_style(is_current: bool) -> Style {
+    let fg = match is_current {
+        true => Color::Indexed(210),
+        false => Color::Indexed(245),
+    };
+    Style::new()
+        .fg(fg)
+        .bg(Color::Reset)
+        .add_modifier(Modifier::ITALIC)
+}
+
+/// 逐字高亮：已唱部分保持当前行样式，未唱部分淡化
+fn karaoke_spans(line: &LyricsLine, current_time: f64) -> Vec<Span<'static>> {
+    let unsung = Style::new().fg(Color::Indexed(245));
+    let mut spans = Vec::with_capacity(line.words.len());
+
+    for word in &line.words {
+        if current_time >= word.timestamp_end {
+            spans.push(Span::raw(word.text.clone()));
+        } else if current_time <= word.timestamp_start {
+            spans.push(Span::styled(word.text.clone(), unsung));
+        } else {
+            // 当前字按时间比例拆分
+            let duration = word.timestamp_end - word.timestamp_start;
+            let ratio = (current_time - word.timestamp_start) / duration;
+            let chars: Vec<char> = word.text.chars().collect();
+            let split = ((chars.len() as f64 * ratio).ceil() as usize).min(chars.len());
+            spans.push(Span::raw(chars[..split].iter().collect::<String>()));
+            spans.push(Span::styled(
+                chars[split..].iter().collect::<String>(),
+                unsung,
+            ));
+        }
+    }
+
+    spans
+}
+
 // 新增显示参数结构体
 #[derive(Debug, Clone, Copy, Default)]
 pub struct ViewMetrics {
@@ -251,25 +427,37 @@ pub struct LyricState {
     /// 播放时间
     pub play_time: PlayTime,
     /// 当前歌词
-    pub lyrics: Vec<LyricsLine>,
+    pub lyrics: Lyrics,
     /// 目标滚动位置
     pub target_scroll: usize,
     /// 新增显示参数
     pub view_metrics: ViewMetrics,
     /// 新增错误状态
     pub error_message: Option<String>,
+    /// 纯音乐（无歌词）
+    pub instrumental: bool,
+    /// 无时间轴歌词按进度自动滚动
+    pub auto_scroll: bool,
     /// 重试计数器
     pub retry_counter: u32,
     /// 进度
     pub progress: f64,
+    /// 用户调整的偏移（毫秒）
+    pub offset: i64,
+    /// 偏移调整后尚未保存的时间点
+    offset_changed: Option<Instant>,
 }
 
+/// 偏移停止调整后等待该时间再保存，避免按键连发时反复写盘
+const OFFSET_SAVE_DELAY: Duration = Duration::from_secs(1);
+
 impl LyricState {
     // 预计算显示参数
     pub fn calculate_metrics(&mut self, area: Size) {
-        let content_height = self.lyrics.len();
+        let content_height = self.lyrics.lines.len();
         let viewport_height = area.height as usize;
-        let visible_lines = viewport_height.saturating_sub(2); // 保留边界空间
+        // 保留边界空间，翻译、罗马音各占一行
+        let visible_lines = viewport_height.saturating_sub(2) / self.rows_per_line();
         let scroll_range = content_height.saturating_sub(visible_lines);
 
         self.view_metrics = ViewMetrics {
@@ -279,11 +467,22 @@ impl LyricState {
         };
     }
 
+    /// 每句歌词占用的行数
+    fn rows_per_line(&self) -> usize {
+        let config = &get_config().read().unwrap().ui;
+        let translation =
+            config.translation == TranslationMode::Below && self.lyrics.has_translation();
+        let romanization =
+            config.romanization == RomanizationMode::Both && self.lyrics.has_romanization();
+        1 + translation as usize + romanization as usize
+    }
+
     pub fn reset(&mut self) {
         *self = LyricState::default();
     }
 
     pub async fn update(&mut self) {
+        self.save_offset(false).await;
         match self.try_update().await {
             Ok(_) => {
                 self.error_message = None; // 清除旧错误        
@@ -302,6 +501,7 @@ impl LyricState {
         let song = match get_current_song().await {
             Ok(s) => s,
             Err(e) => {
+                self.save_offset(true).await;
                 self.reset();
                 return Err(e);
             }
@@ -309,25 +509,34 @@ impl LyricState {
 
         // 歌曲发生变化时重新加载歌词
         if song != self.song {
+            self.save_offset(true).await;
+            self.offset = 0;
             // 切歌时清除错误状态和重试计数
             self.error_message = None;
             self.retry_counter = 0;
 
             self.song = song.clone();
+            self.lyrics = Lyrics::default();
+            self.instrumental = false;
+            self.target_scroll = 0;
+            {
+                let config = get_config().read().unwrap();
+                self.auto_scroll = config.ui.unsynced_auto_scroll;
+                self.play_time.latency = config.sync.latency_for(&song.player);
+            }
 
-            // 尝试获取歌词
+            // 读取缓存或按分数下载并校验歌词
             match get_lyrics_client().get_lyrics(&song).await {
                 Ok(doc) => {
-                    self.lyrics = LyricParser::parse(doc, song.duration).await?;
-                }
-                Err(LyricsError::NoLyricsFound) => {
-                    // 没有找到歌词，尝试自动搜索并下载
-                    info!(
-                        "No lyrics found, attempting auto-download for: {} - {}",
-                        song.artist, song.title
-                    );
-                    self.auto_download_lyrics(&song).await?;
+                    self.lyrics = LyricParser::parse_doc(doc, song.duration).await?;
+                    // 用户调整的偏移叠加在歌词自带的 [offset:] 上
+                    if !matches!(song.track_id, TrackId::File(_)) {
+                        self.offset = get_lyrics_client().cache.offset(&song).await;
+                        self.lyrics.shift_offset(self.offset);
+                    }
                 }
+                // 纯音乐不再重试
+                Err(LyricsError::Instrumental) => self.instrumental = true,
                 Err(e) => return Err(e),
             }
         }
@@ -336,8 +545,14 @@ impl LyricState {
         self.play_time = get_current_time_song(self.play_time.clone()).await?;
         self.progress = self.play_time.current_time / song.duration;
 
-        // 更新滚动位置
-        if let Some(pos) = self.find_current_line() {
+        // 更新滚动位置，无时间轴歌词按进度比例滚动或手动滚动
+        if self.lyrics.unsynced {
+            if self.auto_scroll {
+                let ratio = self.progress.clamp(0.0, 1.0);
+                self.target_scroll =
+                    (ratio * self.view_metrics.scroll_range as f64).round() as usize;
+            }
+        } else if let Some(pos) = self.find_current_line() {
             let target_offset = pos.saturating_sub(self.view_metrics.visible_lines / 2);
             self.target_scroll = target_offset.min(self.view_metrics.scroll_range);
         }
@@ -345,37 +560,52 @@ impl LyricState {
         Ok(())
     }
 
-    async fn auto_download_lyrics(&mut self, song: &SongInfo) -> Result<(), LyricsError> {
-        // 搜索歌词列表
-        let search_results = get_lyrics_client().get_search(song).await?;
-
-        if search_results.is_empty() {
-            warn!("Auto-download failed: No search results found");
-            return Err(LyricsError::NoLyricsFound);
+    /// 手动滚动，仅用于无时间轴歌词
+    pub fn scroll_by(&mut self, delta: isize) {
+        if !self.lyrics.unsynced {
+            return;
         }
+        self.auto_scroll = false;
+        self.target_scroll = self
+            .target_scroll
+            .saturating_add_signed(delta)
+            .min(self.view_metrics.scroll_range);
+    }
 
-        // 获取第一个匹配的歌词
-        let best_match = super::super::client::get_first(search_results, song)?;
-
-        info!(
-            "Auto-downloading lyrics from {}: {} - {}",
-            best_match.source, best_match.artist, best_match.title
-        );
-
-        // 下载歌词
-        get_lyrics_client().download(song, &best_match).await?;
+    /// 调整歌词时间偏移，停止调整后保存到缓存，下次播放时生效
+    pub fn adjust_offset(&mut self, delta: i64) {
+        if self.song.title.is_empty() || self.lyrics.lines.is_empty() || self.lyrics.unsynced {
+            return;
+        }
+        self.lyrics.shift_offset(delta);
+        // 离线播放的歌词文件由用户指定，不写入缓存
+        if matches!(self.song.track_id, TrackId::File(_)) {
+            return;
+        }
+        self.offset += delta;
+        self.offset_changed = Some(Instant::now());
+    }
 
-        // 重新加载已下载的歌词
-        let doc = get_lyrics_client().get_lyrics(song).await?;
-        self.lyrics = LyricParser::parse(doc, song.duration).await?;
+    /// 保存未写入的偏移，`force` 为 false 时等待调整停止
+    pub async fn save_offset(&mut self, force: bool) {
+        let Some(changed) = self.offset_changed else {
+            return;
+        };
+        if !force && changed.elapsed() < OFFSET_SAVE_DELAY {
+            return;
+        }
+        self.offset_changed = None;
 
-        info!("Auto-download successful");
-        Ok(())
+        let cache = &get_lyrics_client().cache;
+        if let Err(e) = cache.store_offset(&self.song, self.offset).await {
+            tracing::error!("Save offset: {e}");
+        }
     }
 
     /// 当前播放的 line
     pub fn find_current_line(&self) -> Option<usize> {
         self.lyrics
+            .lines
             .iter()
             .enumerate()
             .find(|(_, line)| {
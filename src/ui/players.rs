@@ -0,0 +1,207 @@
+use std::{
+    borrow::Cow,
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{
+        Stylize,
+        palette::tailwind::{BLUE, GREEN},
+    },
+    text::{Line, Span},
+    widgets::{
+        Block, HighlightSpacing, List, ListItem, ListState, Paragraph, StatefulWidget, Widget,
+    },
+};
+use rust_i18n::t;
+
+use crate::player::{
+    PinnedPlayer, PlaybackStatus, PlayerEntry, list_players, pin_player, pinned_player,
+};
+
+use super::*;
+
+// 播放器选择
+#[derive(Clone, Default)]
+pub(super) struct PlayersScreen {
+    state: PlayersState,
+    list_state: ListState,
+}
+
+impl PlayersScreen {
+    pub fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let err_height = if self.state.error_message.is_some() {
+            Constraint::Length(3)
+        } else {
+            Constraint::Length(0)
+        };
+
+        let [header_chunk, err_chunk, list_chunk, footer_chunk] = Layout::new(
+            Direction::Vertical,
+            [
+                Constraint::Length(1),
+                err_height,
+                Constraint::Min(3),
+                Constraint::Length(1),
+            ],
+        )
+        .areas(area);
+
+        Paragraph::new(t!("players.title"))
+            .bold()
+            .centered()
+            .render(header_chunk, buf);
+        if let Some(err_msg) = &self.state.error_message {
+            render_error(err_chunk, buf, err_msg);
+        }
+        self.render_list(list_chunk, buf);
+        Paragraph::new(t!("players.footer"))
+            .centered()
+            .render(footer_chunk, buf);
+    }
+
+    pub async fn handle_key_event(&mut self, key_event: &KeyEvent) {
+        match key_event.code {
+            KeyCode::Char('l') | KeyCode::Enter => self.pin(false),
+            KeyCode::Char('w') => self.pin(true),
+            KeyCode::Char('u') => self.state.pin(None, false),
+            KeyCode::Char('r') => self.state.refresh().await,
+            KeyCode::Up | KeyCode::Char('p') | KeyCode::Char('k') => {
+                self.list_state.select_previous()
+            }
+            KeyCode::Down | KeyCode::Char('n') | KeyCode::Char('j') => {
+                self.list_state.select_next()
+            }
+            _ => {}
+        }
+    }
+
+    pub fn help<'a>() -> Vec<(&'a str, Cow<'a, str>)> {
+        vec![
+            ("q | ESC ", t!("help.players.back")),
+            ("n | Down", t!("help.players.next")),
+            ("p | Up  ", t!("help.players.prev")),
+            ("l | Enter ", t!("help.players.pin")),
+            ("w", t!("help.players.save")),
+            ("u", t!("help.players.unpin")),
+            ("r", t!("help.players.refresh")),
+        ]
+    }
+
+    fn render_list(&mut self, area: Rect, buf: &mut Buffer) {
+        let block = Block::new().bg(NORMAL_ROW_BG);
+        let pinned = pinned_player();
+
+        let items: Vec<ListItem> = self
+            .state
+            .list
+            .iter()
+            .enumerate()
+            .map(|(i, entry)| {
+                let marker = match Some(&entry.pin) == pinned.as_ref() {
+                    true => "* ",
+                    false => "  ",
+                };
+                let protocol = match entry.pin {
+                    PinnedPlayer::Mpd => "MPD",
+                    PinnedPlayer::Cmus => "cmus",
+                    PinnedPlayer::Mpris(_) => "MPRIS",
+                };
+                let status = match entry.status {
+                    Some(PlaybackStatus::Playing) => t!("players.playing"),
+                    Some(PlaybackStatus::Paused) => t!("players.paused"),
+                    Some(PlaybackStatus::Stopped) => t!("players.stopped"),
+                    None => t!("players.disconnected"),
+                };
+                let song = entry
+                    .song
+                    .as_ref()
+                    .map(|s| format!("{} - {}", s.artist, s.title))
+                    .unwrap_or_default();
+
+                Line::from(vec![
+                    Span::raw(marker),
+                    Span::raw(protocol).fg(BLUE.c400),
+                    Span::raw(" "),
+                    Span::raw(entry.name.clone())
+                        .fg(YELLOW.c400)
+                        .add_modifier(Modifier::BOLD),
+                    Span::raw(format!(" [{}] ", status)),
+                    Span::raw(song).fg(GREEN.c400),
+                ])
+                .bg(alternate_colors(i))
+                .into()
+            })
+            .collect();
+
+        let list = List::new(items)
+            .block(block)
+            .highlight_style(SELECTED_STYLE)
+            .highlight_symbol(">>>")
+            .highlight_spacing(HighlightSpacing::Always);
+
+        StatefulWidget::render(list, area, buf, &mut self.list_state);
+    }
+
+    fn pin(&mut self, persist: bool) {
+        let entry = self
+            .list_state
+            .selected()
+            .and_then(|i| self.state.list.get(i));
+        if let Some(entry) = entry {
+            self.state.pin(Some(entry.pin.clone()), persist);
+        }
+    }
+
+    pub async fn update(&mut self) {
+        self.state.update().await;
+        if self.list_state.selected().is_none() && !self.state.list.is_empty() {
+            self.list_state.select(Some(0));
+        }
+    }
+
+    /// 固定的播放器变化后需要重新加载歌词
+    pub fn player_changed(&mut self) -> bool {
+        std::mem::take(&mut self.state.player_changed)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct PlayersState {
+    list: Vec<PlayerEntry>,
+    error_message: Option<String>,
+    refreshed_at: Option<Instant>,
+    player_changed: bool,
+}
+
+impl PlayersState {
+    /// 列表刷新间隔，避免每帧扫描总线
+    const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+    pub async fn update(&mut self) {
+        if self
+            .refreshed_at
+            .is_none_or(|t| t.elapsed() >= Self::REFRESH_INTERVAL)
+        {
+            self.refresh().await;
+        }
+    }
+
+    async fn refresh(&mut self) {
+        self.list = list_players().await;
+        self.refreshed_at = Some(Instant::now());
+    }
+
+    fn pin(&mut self, player: Option<PinnedPlayer>, persist: bool) {
+        match pin_player(player, persist) {
+            Ok(_) => {
+                self.error_message = None;
+                self.player_changed = true;
+            }
+            Err(e) => self.error_message = Some(e.to_string()),
+        }
+    }
+}
@@ -6,7 +6,7 @@ use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{
         Stylize,
-        palette::tailwind::{BLUE, GREEN},
+        palette::tailwind::{BLUE, GREEN, RED},
     },
     text::{Line, Span},
     widgets::{
@@ -17,6 +17,7 @@ use rust_i18n::t;
 
 use crate::{
     client::{LyricsItem, get_lyrics_client},
+    config::get_config,
     error::LyricsError,
     song::{SongInfo, get_current_song},
 };
@@ -103,14 +104,21 @@ impl SearchScreen {
                 })
                 .collect()
         } else {
+            let min_score = get_config().read().unwrap().sources.min_score;
             self.state
                 .list
                 .iter()
                 .enumerate()
                 .map(|(i, item)| {
                     let color = alternate_colors(i);
+                    // 低于最低分数的候选不会被自动选择
+                    let score_color = match item.score >= min_score {
+                        true => GREEN.c400,
+                        false => RED.c400,
+                    };
 
                     Line::from(vec![
+                        Span::raw(format!("{:>3.0} ", item.score * 100.0)).fg(score_color),
                         Span::raw(&item.source).fg(BLUE.c400),
                         Span::raw(" "),
                         Span::raw(&item.title)
@@ -120,6 +128,7 @@ impl SearchScreen {
                         Span::raw(&item.artist).fg(GREEN.c400),
                         Span::raw(" "),
                         Span::raw(&item.album).add_modifier(Modifier::ITALIC),
+                        Span::raw(item_details(item)).fg(GRAY.c500),
                     ])
                     .bg(color)
                     .into()
@@ -167,6 +176,29 @@ impl SearchScreen {
     }
 }
 
+/// 时长与可提供的歌词类型
+fn item_details(item: &LyricsItem) -> String {
+    let mut details = Vec::new();
+    if let Some(duration) = item.duration {
+        let secs = duration.round() as u64;
+        details.push(format!("{}:{:02}", secs / 60, secs % 60));
+    }
+    if item.kinds.word {
+        details.push(t!("search.word").to_string());
+    }
+    if item.kinds.translation {
+        details.push(t!("search.translation").to_string());
+    }
+    if item.kinds.plain && !item.kinds.synced {
+        details.push(t!("search.plain").to_string());
+    }
+
+    match details.is_empty() {
+        true => String::new(),
+        false => format!(" [{}]", details.join(" ")),
+    }
+}
+
 #[derive(Clone, Default)]
 pub struct SearchState {
     song: SongInfo,